@@ -0,0 +1,185 @@
+use heuristics::Heuristic;
+use node::{Node, NodeError};
+use solver;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub struct Report {
+    pub size: usize,
+    pub moves: usize,
+    pub stats: solver::Stats,
+    pub elapsed: Duration,
+}
+
+fn is_separator(line: &str) -> bool {
+    let before_comment = line.split('#').next().unwrap_or("").trim();
+    line.trim().is_empty() || before_comment == "---"
+}
+
+fn has_content(chunk: &str) -> bool {
+    chunk.lines().any(|l| !l.split('#').next().unwrap_or("").trim().is_empty())
+}
+
+/// Splits a container file into boards. Boards are separated by blank lines
+/// or `---` lines, and each one is parsed on its own with `FromStr for Node`.
+pub fn parse(s: &str) -> Vec<Result<Node, NodeError>> {
+    let mut chunks: Vec<String> = vec![String::new()];
+
+    for line in s.lines() {
+        if is_separator(line) {
+            chunks.push(String::new());
+        } else if let Some(chunk) = chunks.last_mut() {
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+    }
+
+    chunks.iter()
+        .filter(|c| has_content(c))
+        .map(|c| c.parse::<Node>())
+        .collect()
+}
+
+pub fn solve_one(board: Result<Node, NodeError>, h: &Heuristic) -> Result<Report, String> {
+    let n = board.map_err(|e| e.to_string())?;
+    let size = n.len;
+    let start = Instant::now();
+
+    match solver::astar(n, h) {
+        Some((node, stats)) => Ok(Report {
+            size,
+            moves: node.cost,
+            stats,
+            elapsed: start.elapsed(),
+        }),
+        None => Err("no solution found".to_string()),
+    }
+}
+
+/// Solves every board, spreading them over `jobs` worker threads. Results are
+/// returned in input order regardless of which thread finished first.
+pub fn run(boards: Vec<Result<Node, NodeError>>, h: &Heuristic, jobs: usize) -> Vec<Result<Report, String>> {
+    if jobs <= 1 {
+        return boards.into_iter().map(|b| solve_one(b, h)).collect();
+    }
+
+    let count = boards.len();
+    let queue = Arc::new(Mutex::new(boards.into_iter().enumerate()));
+    let (tx, rx) = mpsc::channel();
+
+    let workers = (0..jobs.min(count)).map(|_| {
+        let queue = queue.clone();
+        let tx = tx.clone();
+        let h = h.clone();
+        thread::spawn(move || loop {
+            let next = queue.lock().expect("batch queue poisoned").next();
+            match next {
+                Some((i, board)) => {
+                    if tx.send((i, solve_one(board, &h))).is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        })
+    }).collect::<Vec<_>>();
+    drop(tx);
+
+    let mut results = rx.iter().collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().expect("batch worker panicked");
+    }
+
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+pub fn print_summary(results: &[Result<Report, String>]) {
+    println!("{:<5} {:<5} {:<8} {:>7} {:>12} {:>12} {:>10}",
+             "#", "size", "status", "moves", "selected", "max memory", "time (ms)");
+
+    for (i, res) in results.iter().enumerate() {
+        match *res {
+            Ok(ref r) => {
+                let ms = r.elapsed.as_secs() * 1000 + u64::from(r.elapsed.subsec_millis());
+                println!("{:<5} {:<5} {:<8} {:>7} {:>12} {:>12} {:>10}",
+                         i + 1,
+                         format!("{0}x{0}", r.size),
+                         "solved",
+                         r.moves,
+                         r.stats.total_states,
+                         r.stats.max_states,
+                         ms);
+            }
+            Err(ref e) => println!("{:<5} {:<5} {:<8} {}", i + 1, "-", "failed", e),
+        }
+    }
+
+    let solved = results.iter().filter(|r| r.is_ok()).count();
+    println!();
+    println!("{} puzzles: {} solved, {} failed", results.len(), solved, results.len() - solved);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, run};
+    use heuristics::Heuristic;
+    use node::NodeError;
+
+    #[test]
+    fn batch_parsing_test() {
+        let batch =
+"
+# two boards and an unsolvable one
+3
+1   2   3
+8   0   4
+7   6   5
+
+3
+1   2   3
+8   4   0
+7   6   5
+---
+3
+2   1   3
+8   0   4
+7   6   5
+";
+        let boards = parse(batch);
+        assert!(boards.len() == 3);
+        assert!(boards[0].as_ref().unwrap().board == vec![1, 2, 3, 8, 0, 4, 7, 6, 5]);
+        assert!(boards[1].as_ref().unwrap().board == vec![1, 2, 3, 8, 4, 0, 7, 6, 5]);
+        match boards[2] {
+            Err(NodeError::UnsolvableError) => {}
+            _ => panic!("third board should be unsolvable"),
+        }
+    }
+
+    #[test]
+    fn parallel_batch_keeps_input_order() {
+        let batch =
+"
+3
+1   2   3
+8   4   0
+7   6   5
+---
+3
+2   1   3
+8   0   4
+7   6   5
+---
+3
+0   4   3
+8   1   7
+2   5   6
+";
+        let results = run(parse(batch), &Heuristic::parse("manhattan").unwrap(), 3);
+        assert!(results.len() == 3);
+        assert!(results[0].as_ref().unwrap().moves == 1);
+        assert!(results[1].is_err());
+        assert!(results[2].as_ref().unwrap().moves == 22);
+    }
+}
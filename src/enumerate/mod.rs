@@ -0,0 +1,202 @@
+//! Exhaustive breadth-first enumeration of every board reachable from
+//! `Node::goal`. The resulting distance table is a perfect heuristic and
+//! an oracle for checking the other ones.
+
+use node::Node;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"NPDT";
+const UNREACHABLE: u8 = u8::MAX;
+
+pub struct DistanceTable {
+    pub len: usize,
+    pub distances: Vec<u8>,
+}
+
+fn factorial(n: usize) -> usize {
+    (1..n + 1).product()
+}
+
+/// Lehmer code of the board, a dense index in `0..(len * len)!`.
+pub fn rank(board: &[usize]) -> usize {
+    let mut res = 0;
+
+    for (i, &v) in board.iter().enumerate() {
+        let smaller = board[i + 1..].iter().filter(|&&w| w < v).count();
+        res = res * (board.len() - i) + smaller;
+    }
+
+    res
+}
+
+pub fn neighbours(board: &[usize], len: usize) -> Vec<Vec<usize>> {
+    let pos = board.iter().position(|&v| v == 0).unwrap();
+    let (x, y) = (pos / len, pos % len);
+    let mut res = Vec::with_capacity(4);
+
+    let mut push = |target: usize| {
+        let mut b = board.to_vec();
+        b.swap(pos, target);
+        res.push(b);
+    };
+
+    if y != 0 {
+        push(pos - 1);
+    }
+    if y != len - 1 {
+        push(pos + 1);
+    }
+    if x != len - 1 {
+        push(pos + len);
+    }
+    if x != 0 {
+        push(pos - len);
+    }
+
+    res
+}
+
+impl DistanceTable {
+    pub fn build(len: usize) -> DistanceTable {
+        let goal = Node::goal(len);
+        let mut distances = vec![UNREACHABLE; factorial(len * len)];
+        let mut queue = VecDeque::new();
+
+        distances[rank(&goal.board)] = 0;
+        queue.push_back(goal.board);
+
+        while let Some(board) = queue.pop_front() {
+            let d = distances[rank(&board)];
+            for next in neighbours(&board, len) {
+                let r = rank(&next);
+                if distances[r] == UNREACHABLE {
+                    distances[r] = d + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        DistanceTable {
+            len,
+            distances,
+        }
+    }
+
+    pub fn get(&self, board: &[usize]) -> Option<usize> {
+        match self.distances.get(rank(board)) {
+            Some(&d) if d != UNREACHABLE => Some(d as usize),
+            _ => None,
+        }
+    }
+
+    pub fn reachable(&self) -> usize {
+        self.distances.iter().filter(|&&d| d != UNREACHABLE).count()
+    }
+
+    /// Number of boards at each distance from the goal.
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut res = Vec::new();
+
+        for &d in self.distances.iter().filter(|&&d| d != UNREACHABLE) {
+            if res.len() <= d as usize {
+                res.resize(d as usize + 1, 0);
+            }
+            res[d as usize] += 1;
+        }
+
+        res
+    }
+
+    /// Calls `f` with every reachable board and its distance to the goal.
+    pub fn for_each<F: FnMut(&[usize], usize)>(&self, mut f: F) {
+        let mut board: Vec<usize> = (0..self.len * self.len).collect();
+
+        // Boards are visited in lexicographic order, which is also rank order.
+        for &d in &self.distances {
+            if d != UNREACHABLE {
+                f(&board, d as usize);
+            }
+            next_permutation(&mut board);
+        }
+    }
+
+    /// Every board sitting at the maximum distance from the goal.
+    pub fn hardest(&self) -> Vec<Node> {
+        let max = self.histogram().len().saturating_sub(1);
+        let mut res = Vec::new();
+
+        self.for_each(|board, d| {
+            if d == max {
+                res.push(Node {
+                    board: board.to_vec(),
+                    len: self.len,
+                    cost: 0,
+                    heuristic: max,
+                    parents: None,
+                });
+            }
+        });
+
+        res
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(MAGIC)?;
+        file.write_all(&[self.len as u8])?;
+        file.write_all(&self.distances)
+    }
+
+    pub fn load(path: &str) -> io::Result<DistanceTable> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 5 || &bytes[..4] != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a distance table"));
+        }
+
+        let len = bytes[4] as usize;
+        let distances = bytes.split_off(5);
+        if distances.len() != factorial(len * len) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated distance table"));
+        }
+
+        Ok(DistanceTable {
+            len,
+            distances,
+        })
+    }
+}
+
+fn next_permutation(board: &mut [usize]) -> bool {
+    let i = match (1..board.len()).rev().find(|&i| board[i - 1] < board[i]) {
+        Some(i) => i,
+        None => return false,
+    };
+    let j = (i..board.len()).rev().find(|&j| board[j] > board[i - 1]).unwrap();
+    board.swap(i - 1, j);
+    board[i..].reverse();
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::{rank, DistanceTable};
+    use node::Node;
+
+    #[test]
+    fn enumerate_3x3_test() {
+        let table = DistanceTable::build(3);
+        assert!(table.reachable() == 181_440);
+        assert!(table.histogram().len() == 31);
+        assert!(table.get(&Node::goal(3).board) == Some(0));
+        assert!(table.get(&[1, 2, 3, 8, 4, 0, 7, 6, 5]) == Some(1));
+
+        for n in table.hardest() {
+            assert!(rank(&n.board) < table.distances.len());
+            assert!(table.get(&n.board) == Some(30));
+        }
+    }
+}
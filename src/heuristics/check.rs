@@ -0,0 +1,137 @@
+//! Checks a heuristic against the exact distances of an enumerated board
+//! space, so A* optimality claims can be verified instead of assumed.
+
+use enumerate::{self, DistanceTable};
+use heuristics::Heuristic;
+use node::Node;
+
+pub struct Report {
+    pub states: usize,
+    pub overestimates: usize,
+    /// Board with the largest overestimate, with its h and true distance.
+    pub worst_overestimate: Option<(Vec<usize>, usize, usize)>,
+    pub inconsistencies: usize,
+    /// Move with the largest drop in h, as (from, to, h(from), h(to)).
+    pub worst_inconsistency: Option<(Vec<usize>, Vec<usize>, usize, usize)>,
+    pub mean_gap: f64,
+    pub mean_distance: f64,
+}
+
+/// Share of the true distance a heuristic has to recover on average to be
+/// worth its evaluation cost.
+const INFORMATIVE: f64 = 0.1;
+
+impl Report {
+    pub fn is_admissible(&self) -> bool {
+        self.overestimates == 0
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies == 0
+    }
+
+    /// False for a heuristic that is always 0, or close enough that the
+    /// search is barely better than uniform cost.
+    pub fn is_informative(&self) -> bool {
+        self.mean_distance - self.mean_gap >= INFORMATIVE * self.mean_distance
+    }
+}
+
+fn evaluate(h: &Heuristic, board: &[usize], len: usize) -> usize {
+    h.eval(&Node {
+        board: board.to_vec(),
+        len,
+        cost: 0,
+        heuristic: 0,
+        parents: None,
+    })
+}
+
+pub fn check(h: &Heuristic, table: &DistanceTable) -> Report {
+    let mut values = vec![0; table.distances.len()];
+    let mut report = Report {
+        states: 0,
+        overestimates: 0,
+        worst_overestimate: None,
+        inconsistencies: 0,
+        worst_inconsistency: None,
+        mean_gap: 0.0,
+        mean_distance: 0.0,
+    };
+    let mut total_gap = 0_i64;
+    let mut total_distance = 0_usize;
+
+    table.for_each(|board, d| {
+        let value = evaluate(h, board, table.len);
+        values[enumerate::rank(board)] = value;
+        report.states += 1;
+        total_gap += d as i64 - value as i64;
+        total_distance += d;
+
+        if value > d {
+            report.overestimates += 1;
+            let worse = report.worst_overestimate.as_ref()
+                .map(|&(_, wh, wd)| value - d > wh - wd)
+                .unwrap_or(true);
+            if worse {
+                report.worst_overestimate = Some((board.to_vec(), value, d));
+            }
+        }
+    });
+
+    table.for_each(|board, _| {
+        let from = values[enumerate::rank(board)];
+        for next in enumerate::neighbours(board, table.len) {
+            let to = values[enumerate::rank(&next)];
+            if from > to + 1 {
+                report.inconsistencies += 1;
+                let worse = report.worst_inconsistency.as_ref()
+                    .map(|&(_, _, wf, wt)| from - to > wf - wt)
+                    .unwrap_or(true);
+                if worse {
+                    report.worst_inconsistency = Some((board.to_vec(), next, from, to));
+                }
+            }
+        }
+    });
+
+    if report.states > 0 {
+        report.mean_gap = total_gap as f64 / report.states as f64;
+        report.mean_distance = total_distance as f64 / report.states as f64;
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::check;
+    use enumerate::DistanceTable;
+    use heuristics::{self, Heuristic};
+    use node::Node;
+
+    fn zero(_: &Node) -> usize {
+        0
+    }
+
+    #[test]
+    fn check_heuristic_test() {
+        let table = DistanceTable::build(2);
+
+        let report = check(&Heuristic::Base("zero", zero), &table);
+        assert!(report.states == 12);
+        assert!(report.is_admissible() && report.is_consistent());
+        assert!(report.mean_gap > 0.0);
+        assert!(!report.is_informative());
+
+        let report = check(&Heuristic::Base("exact", heuristics::eval_exact), &table);
+        assert!(report.is_admissible() && report.is_consistent());
+        assert!(report.mean_gap == 0.0);
+        assert!(report.is_informative());
+
+        // Counting the blank makes a board one move away from the goal score 2
+        let report = check(&Heuristic::Base("misplaced", heuristics::eval_misplaced), &table);
+        assert!(!report.is_admissible());
+        assert!(report.worst_overestimate.is_some());
+    }
+}
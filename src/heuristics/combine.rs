@@ -0,0 +1,238 @@
+//! Heuristic expressions as accepted by `--heuristic`: a single name, a
+//! `max(a,b,...)` of expressions, or a sum `a+b`. Sums are only accepted
+//! between admissible components that are known to be disjoint, otherwise
+//! the total could overestimate and A* would lose its optimality.
+
+use heuristics;
+use node::Node;
+use std::fmt;
+
+struct Base {
+    name: &'static str,
+    eval: fn(&Node) -> usize,
+    admissible: bool,
+}
+
+const BASES: &[Base] = &[
+    // The tile distances below also count the blank, which overestimates.
+    Base { name: "manhattan", eval: heuristics::eval_manhattan, admissible: false },
+    Base { name: "tiles", eval: heuristics::eval_tiles, admissible: true },
+    Base { name: "euclide", eval: heuristics::eval_euclide, admissible: false },
+    Base { name: "misplaced", eval: heuristics::eval_misplaced, admissible: false },
+    Base { name: "conflict", eval: heuristics::eval_conflict, admissible: true },
+    Base { name: "exact", eval: heuristics::eval_exact, admissible: true },
+];
+
+/// Pairs of components that never count the same move twice.
+const DISJOINT: &[(&str, &str)] = &[
+    ("tiles", "conflict"),
+];
+
+#[derive(Clone)]
+pub enum Heuristic {
+    Base(&'static str, fn(&Node) -> usize),
+    Max(Vec<Heuristic>),
+    Sum(Vec<Heuristic>),
+}
+
+impl Heuristic {
+    pub fn eval(&self, n: &Node) -> usize {
+        match *self {
+            Heuristic::Base(_, h) => h(n),
+            Heuristic::Max(ref hs) => hs.iter().map(|h| h.eval(n)).max().unwrap_or(0),
+            Heuristic::Sum(ref hs) => hs.iter().map(|h| h.eval(n)).sum(),
+        }
+    }
+
+    pub fn is_admissible(&self) -> bool {
+        match *self {
+            Heuristic::Base(name, _) => BASES.iter().any(|b| b.name == name && b.admissible),
+            Heuristic::Max(ref hs) | Heuristic::Sum(ref hs) => hs.iter().all(|h| h.is_admissible()),
+        }
+    }
+
+    fn components(&self) -> Vec<&'static str> {
+        match *self {
+            Heuristic::Base(name, _) => vec![name],
+            Heuristic::Max(ref hs) | Heuristic::Sum(ref hs) => {
+                hs.iter().flat_map(|h| h.components()).collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Heuristic::Base(name, _) => name.fmt(f),
+            Heuristic::Max(ref hs) => {
+                let parts = hs.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+                write!(f, "max({})", parts.join(","))
+            }
+            Heuristic::Sum(ref hs) => {
+                let parts = hs.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+                parts.join("+").fmt(f)
+            }
+        }
+    }
+}
+
+fn disjoint(a: &str, b: &str) -> bool {
+    DISJOINT.iter().any(|&(x, y)| (x == a && y == b) || (x == b && y == a))
+}
+
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in s.char_indices() {
+        if c.is_alphanumeric() || c == '_' {
+            if start.is_none() {
+                start = Some(i);
+            }
+            continue;
+        }
+        if let Some(st) = start.take() {
+            tokens.push(&s[st..i]);
+        }
+        if !c.is_whitespace() {
+            tokens.push(&s[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(st) = start {
+        tokens.push(&s[st..]);
+    }
+
+    tokens
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        match self.peek() {
+            Some(t) if t == token => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => Err(format!("expected '{}', found '{}'", token, t)),
+            None => Err(format!("expected '{}' at end of heuristic", token)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Heuristic, String> {
+        let mut terms = vec![self.term()?];
+
+        while self.peek() == Some("+") {
+            self.pos += 1;
+            terms.push(self.term()?);
+        }
+
+        if terms.len() == 1 {
+            return Ok(terms.remove(0));
+        }
+
+        for t in &terms {
+            if !t.is_admissible() {
+                return Err(format!("{} is not admissible and cannot be summed", t));
+            }
+        }
+        for (i, a) in terms.iter().enumerate() {
+            for b in &terms[i + 1..] {
+                for ca in a.components() {
+                    for cb in b.components() {
+                        if !disjoint(ca, cb) {
+                            return Err(format!("{} and {} are not disjoint and cannot be summed", ca, cb));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Heuristic::Sum(terms))
+    }
+
+    fn term(&mut self) -> Result<Heuristic, String> {
+        let token = self.peek().ok_or_else(|| "unexpected end of heuristic".to_string())?;
+        self.pos += 1;
+
+        if token == "(" {
+            let inner = self.sum()?;
+            self.expect(")")?;
+            return Ok(inner);
+        }
+
+        if token.to_lowercase() == "max" {
+            self.expect("(")?;
+            let mut args = vec![self.sum()?];
+            while self.peek() == Some(",") {
+                self.pos += 1;
+                args.push(self.sum()?);
+            }
+            self.expect(")")?;
+            return Ok(Heuristic::Max(args));
+        }
+
+        let name = token.to_lowercase();
+        BASES.iter()
+            .find(|b| b.name == name)
+            .map(|b| Heuristic::Base(b.name, b.eval))
+            .ok_or_else(|| format!("unknown heuristic '{}'", token))
+    }
+}
+
+impl Heuristic {
+    pub fn parse(s: &str) -> Result<Heuristic, String> {
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            pos: 0,
+        };
+        let h = parser.sum()?;
+
+        match parser.peek() {
+            None => Ok(h),
+            Some(t) => Err(format!("unexpected '{}' in heuristic", t)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heuristic;
+    use heuristics;
+    use node::Node;
+
+    #[test]
+    fn combinator_parsing_test() {
+        let n: Node = "3\n0 4 3\n8 1 7\n2 5 6\n".parse().expect("Map should be valid");
+
+        let h = Heuristic::parse("max(manhattan, misplaced)").expect("max should parse");
+        assert!(h.eval(&n) == heuristics::eval_manhattan(&n).max(heuristics::eval_misplaced(&n)));
+        assert!(h.to_string() == "max(manhattan,misplaced)");
+
+        let h = Heuristic::parse("MAX(exact,(conflict))").expect("nested max should parse");
+        assert!(h.is_admissible());
+
+        let h = Heuristic::parse("tiles+conflict").expect("disjoint sum should parse");
+        assert!(h.is_admissible());
+        assert!(h.eval(&n) == heuristics::eval_tiles(&n) + heuristics::eval_conflict(&n));
+        assert!(h.to_string() == "tiles+conflict");
+
+        // 1 has to leave the top row for 2 and 3 to pass: two more moves
+        let reversed: Node = "3\n2 3 1\n8 0 4\n7 6 5\n".parse().expect("Map should be valid");
+        assert!(h.eval(&reversed) == heuristics::eval_tiles(&reversed) + 2);
+
+        assert!(Heuristic::parse("manhattan+conflict").is_err());
+        assert!(Heuristic::parse("exact+conflict").is_err());
+        assert!(Heuristic::parse("pdb").is_err());
+        assert!(Heuristic::parse("max(manhattan").is_err());
+        assert!(Heuristic::parse("manhattan)").is_err());
+    }
+}
@@ -1,27 +1,80 @@
+pub mod check;
+pub mod combine;
+
+pub use self::combine::Heuristic;
+
+use enumerate::DistanceTable;
 use node;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+pub const NAMES: &[&str] = &["manhattan", "tiles", "euclide", "conflict", "misplaced", "exact"];
+
+pub fn usage(h: &str, e: &str) -> String {
+    format!("Invalid value for heuritic: {}: {}, possible values are: \
+            \n\tmanhattan: Manhattan distance \
+            \n\ttiles: Manhattan distance of the tiles, blank excluded \
+            \n\teuclide: Euclidean distance \
+            \n\tconflict: Linear Conflict \
+            \n\tmisplaced: Misplaced tiles \
+            \n\texact: Exact distance table (up to 3x3, tiles above) \
+            \n\tmax(a,b,...): Maximum of several heuristics \
+            \n\ta+b: Sum of disjoint admissible heuristics", h, e)
+}
+
+/// Position of every value in the goal, to avoid a search per tile. Tables
+/// are built once per board size.
+fn goal_positions(len: usize) -> Cow<'static, [(usize, usize)]> {
+    static TABLES: [OnceLock<Vec<(usize, usize)>>; 64] = [const { OnceLock::new() }; 64];
+
+    let build = || {
+        let goal = node::Node::goal(len);
+        let mut positions = vec![(0, 0); goal.board.len()];
+        for (i, &v) in goal.board.iter().enumerate() {
+            positions[v] = (i / len, i % len);
+        }
+        positions
+    };
+    match TABLES.get(len) {
+        Some(t) => Cow::Borrowed(t.get_or_init(build)),
+        None => Cow::Owned(build()),
+    }
+}
 
 pub fn eval_manhattan(n: &node::Node) -> usize {
     let mut sum = 0_usize;
-    let goal = node::Node::goal(n.len);
+    let goal = goal_positions(n.len);
 
-    for val in &n.board {
-        let (x, y) = goal.get_pos(*val).unwrap();
-        let (pos_x, pos_y) = n.get_pos(*val).unwrap();
+    for (i, val) in n.board.iter().enumerate() {
+        let (x, y) = goal[*val];
+        let (pos_x, pos_y) = (i / n.len, i % n.len);
 
-        sum += (pos_x as i32 - x as i32).abs() as usize + (pos_y as i32 - y as i32).abs() as usize;
+        sum += (pos_x as i32 - x as i32).unsigned_abs() as usize + (pos_y as i32 - y as i32).unsigned_abs() as usize;
     }
 
     sum
 }
+
+/// Manhattan distance of the tiles, the blank excluded: unlike
+/// `eval_manhattan` it never overestimates, so it can be summed with other
+/// admissible heuristics.
+pub fn eval_tiles(n: &node::Node) -> usize {
+    let goal = goal_positions(n.len);
+
+    n.board.iter().enumerate()
+        .filter(|&(_, &v)| v != 0)
+        .map(|(i, &v)| (i / n.len).abs_diff(goal[v].0) + (i % n.len).abs_diff(goal[v].1))
+        .sum()
+}
 pub fn eval_euclide(n: &node::Node) -> usize {
     let mut sum = 0_usize;
-    let goal = node::Node::goal(n.len);
+    let goal = goal_positions(n.len);
 
-    for val in &n.board {
-        let (x, y) = goal.get_pos(*val).unwrap();
-        let (pos_x, pos_y) = n.get_pos(*val).unwrap();
-        let dx = (pos_x as i32 - x as i32).abs() as usize;
-        let dy = (pos_y as i32 - y as i32).abs() as usize;
+    for (i, val) in n.board.iter().enumerate() {
+        let (x, y) = goal[*val];
+        let (pos_x, pos_y) = (i / n.len, i % n.len);
+        let dx = (pos_x as i32 - x as i32).unsigned_abs() as usize;
+        let dy = (pos_y as i32 - y as i32).unsigned_abs() as usize;
         sum += ((dx * dx + dy * dy) as f64).sqrt() as usize;
     }
 
@@ -41,25 +94,93 @@ pub fn eval_misplaced(n: &node::Node) -> usize {
     sum
 }
 
+/// Linear conflict: two tiles in their goal row, or column, but in reverse
+/// order have to leave the line to pass each other, two moves that the
+/// Manhattan distance of the tiles does not count. Only the tiles outside
+/// the longest run already in order need to leave, which keeps the sum with
+/// `eval_tiles` admissible.
 pub fn eval_conflict(n: &node::Node) -> usize {
+    let goal = goal_positions(n.len);
     let mut sum = 0_usize;
-    for i in 0..n.len {
-        for j in 0..n.len {
-            for k in j..n.len {
-                if conflict(n, i, j, k) {
-                    sum += 2
-                }
+
+    for line in 0..n.len {
+        let row = (0..n.len)
+            .map(|col| n.board[line * n.len + col])
+            .filter(|&v| v != 0 && goal[v].0 == line)
+            .map(|v| goal[v].1)
+            .collect::<Vec<_>>();
+        let col = (0..n.len)
+            .map(|row| n.board[row * n.len + line])
+            .filter(|&v| v != 0 && goal[v].1 == line)
+            .map(|v| goal[v].0)
+            .collect::<Vec<_>>();
+        sum += 2 * (conflicts(&row) + conflicts(&col));
+    }
+    sum
+}
+
+/// Tiles of a line that must leave it, given their goal index along the
+/// line: all but the longest increasing subsequence.
+fn conflicts(targets: &[usize]) -> usize {
+    let mut longest = vec![1_usize; targets.len()];
+    for i in 0..targets.len() {
+        for j in 0..i {
+            if targets[j] < targets[i] {
+                longest[i] = longest[i].max(longest[j] + 1);
             }
         }
     }
-    sum
+    targets.len() - longest.iter().cloned().max().unwrap_or(0)
 }
 
-fn conflict(n: &node::Node, line: usize, col1: usize, col2: usize) -> bool {
-    let ivalue = n.board[line * n.len + col1];
-    let kvalue = n.board[line * n.len + col2];
-    let (ix, iy) = n.get_pos(ivalue).unwrap();
-    let (kx, ky) = n.get_pos(kvalue).unwrap();
+/// Exact distance to the goal, read from the `enumerate` table. The table is
+/// loaded from `distances_N.bin` when present and rebuilt otherwise, which is
+/// only practical for boards up to 3x3. Larger boards get `eval_tiles`, so
+/// the heuristic stays admissible.
+pub fn eval_exact(n: &node::Node) -> usize {
+    static TABLES: [OnceLock<DistanceTable>; 4] = [OnceLock::new(), OnceLock::new(), OnceLock::new(), OnceLock::new()];
+
+    let table = match TABLES.get(n.len) {
+        Some(t) => t,
+        None => return eval_tiles(n),
+    };
+    let table = table.get_or_init(|| {
+        DistanceTable::load(&format!("distances_{}.bin", n.len))
+            .ok()
+            .filter(|t| t.len == n.len)
+            .unwrap_or_else(|| DistanceTable::build(n.len))
+    });
+
+    table.get(&n.board).unwrap_or(0)
+}
 
-    ix == kx && iy > ky
+#[cfg(test)]
+mod test {
+    use super::{eval_conflict, eval_exact, eval_tiles};
+    use node::Node;
+
+    #[test]
+    fn linear_conflict_test() {
+        let n: Node = "3\n1 2 3\n8 0 4\n7 6 5\n".parse().expect("Map should be valid");
+        assert!(eval_conflict(&n) == 0);
+
+        // 1 has to leave the top row to let 2 and 3 pass
+        let n: Node = "3\n2 3 1\n8 0 4\n7 6 5\n".parse().expect("Map should be valid");
+        assert!(eval_conflict(&n) == 2);
+
+        // Three reversed tiles: two of them leave the row, not one per pair
+        let n: Node = "3\n3 2 1\n8 0 4\n6 7 5\n".parse().expect("Map should be valid");
+        assert!(eval_conflict(&n) == 6);
+
+        // 8 and 7 are reversed in their goal column, 5 and 6 in their row
+        let n: Node = "3\n1 2 3\n7 0 4\n8 5 6\n".parse().expect("Map should be valid");
+        assert!(eval_conflict(&n) == 4);
+    }
+
+    #[test]
+    fn exact_falls_back_on_tiles_test() {
+        let n: Node = "4\n1 2 3 4\n12 13 14 5\n11 15 0 6\n10 9 8 7\n".parse().expect("Map should be valid");
+        assert!(eval_exact(&n) == eval_tiles(&n));
+        assert!(eval_exact(&n) == 1);
+    }
 }
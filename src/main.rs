@@ -1,18 +1,36 @@
 extern crate clap;
 extern crate rand;
 extern crate ansi_term;
+extern crate libc;
 
 mod node;
 mod heuristics;
+mod solver;
+mod batch;
+mod enumerate;
+mod verify;
+mod output;
+mod term;
+mod replay;
+mod play;
+mod render;
+mod optimize;
 
 use node::Node;
-use std::collections::{BinaryHeap,HashSet};
 use clap::{Arg, App, SubCommand};
+use std::env;
 use std::fs::File;
-use std::io::Read;
-use std::rc::Rc;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
 fn main() {
+    // Die quietly like other command line tools when piped into `head`
+    #[cfg(unix)]
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+
     let matches = App::new("Npuzzle")
         .about("Taquin solver")
         .subcommand(SubCommand::with_name("generate")
@@ -33,55 +51,372 @@ fn main() {
                     .arg(Arg::with_name("file")
                          .index(1)
                          .value_name("FILE"))
+                    .arg(Arg::with_name("batch")
+                         .long("batch")
+                         .value_name("FILE")
+                         .conflicts_with("file")
+                         .takes_value(true))
+                    .arg(Arg::with_name("jobs")
+                         .long("jobs")
+                         .short("j")
+                         .takes_value(true))
+                    .arg(Arg::with_name("animate")
+                         .long("animate"))
+                    .arg(Arg::with_name("delay")
+                         .long("delay")
+                         .value_name("MS")
+                         .takes_value(true))
+                    .arg(Arg::with_name("dump-tree")
+                         .long("dump-tree")
+                         .value_name("FILE")
+                         .takes_value(true))
+                    .arg(Arg::with_name("dump-limit")
+                         .long("dump-limit")
+                         .requires("dump-tree")
+                         .takes_value(true))
+                    .arg(Arg::with_name("mem-budget")
+                         .long("mem-budget")
+                         .value_name("MB")
+                         .takes_value(true))
+                    .arg(Arg::with_name("scratch-dir")
+                         .long("scratch-dir")
+                         .value_name("DIR")
+                         .takes_value(true))
+                    .arg(Arg::with_name("move-nodes")
+                         .long("move-nodes")
+                         .takes_value(true))
+                    .arg(Arg::with_name("move-time")
+                         .long("move-time")
+                         .value_name("MS")
+                         .takes_value(true))
+                    .arg(Arg::with_name("trials")
+                         .long("trials")
+                         .takes_value(true))
+                    .arg(Arg::with_name("weight")
+                         .long("weight")
+                         .takes_value(true))
+                    .arg(Arg::with_name("beam-width")
+                         .long("beam-width")
+                         .takes_value(true))
+                    .arg(Arg::with_name("open-list")
+                         .long("open-list")
+                         .takes_value(true))
+                    .arg(Arg::with_name("tie-break")
+                         .long("tie-break")
+                         .takes_value(true))
+                    .arg(Arg::with_name("trace")
+                         .long("trace")
+                         .value_name("FILE")
+                         .takes_value(true))
+                    .arg(Arg::with_name("export-gif")
+                         .long("export-gif")
+                         .value_name("FILE")
+                         .takes_value(true))
+                    .arg(Arg::with_name("frame-delay")
+                         .long("frame-delay")
+                         .value_name("MS")
+                         .takes_value(true))
+                    .arg(Arg::with_name("smooth")
+                         .long("smooth"))
+                    .args(&style_args())
+                    .arg(Arg::with_name("output")
+                         .long("output")
+                         .short("o")
+                         .takes_value(true))
+                    .arg(Arg::with_name("algorithm")
+                         .long("algorithm")
+                         .short("a")
+                         .takes_value(true))
+                    .arg(Arg::with_name("heuristic")
+                         .long("heuristic")
+                         .short("h")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("enumerate")
+                    .about("enumerates every reachable state from the goal")
+                    .arg(Arg::with_name("size")
+                         .short("s")
+                         .long("size")
+                         .takes_value(true))
+                    .arg(Arg::with_name("output")
+                         .short("o")
+                         .long("output")
+                         .value_name("FILE")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("check-heuristic")
+                    .about("checks heuristics against exact distances")
                     .arg(Arg::with_name("heuristic")
                          .long("heuristic")
                          .short("h")
-                         .takes_value(true))).get_matches();
+                         .takes_value(true))
+                    .arg(Arg::with_name("size")
+                         .short("s")
+                         .long("size")
+                         .takes_value(true))
+                    .arg(Arg::with_name("table")
+                         .long("table")
+                         .value_name("FILE")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("verify")
+                    .about("checks a solution against a game configuration")
+                    .arg(Arg::with_name("file")
+                         .index(1)
+                         .required(true)
+                         .value_name("PUZZLE"))
+                    .arg(Arg::with_name("solution")
+                         .index(2)
+                         .required(true)
+                         .value_name("SOLUTION"))
+                    .arg(Arg::with_name("optimal")
+                         .long("optimal")))
+        .subcommand(SubCommand::with_name("optimize")
+                    .about("shortens a solution")
+                    .arg(Arg::with_name("file")
+                         .index(1)
+                         .required(true)
+                         .value_name("PUZZLE"))
+                    .arg(Arg::with_name("solution")
+                         .index(2)
+                         .required(true)
+                         .value_name("SOLUTION"))
+                    .arg(Arg::with_name("window")
+                         .long("window")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("replay")
+                    .about("replays a saved solution")
+                    .arg(Arg::with_name("file")
+                         .index(1)
+                         .required(true)
+                         .value_name("PUZZLE"))
+                    .arg(Arg::with_name("solution")
+                         .index(2)
+                         .required(true)
+                         .value_name("SOLUTION"))
+                    .arg(Arg::with_name("delay")
+                         .long("delay")
+                         .value_name("MS")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("play")
+                    .about("plays a game configuration in the terminal")
+                    .arg(Arg::with_name("file")
+                         .index(1)
+                         .value_name("FILE")
+                         .conflicts_with("size"))
+                    .arg(Arg::with_name("size")
+                         .short("s")
+                         .long("size")
+                         .takes_value(true))
+                    .arg(Arg::with_name("iterations")
+                         .short("i")
+                         .long("iterations")
+                         .takes_value(true))
+                    .arg(Arg::with_name("heuristic")
+                         .long("heuristic")
+                         .short("h")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("render")
+                    .about("renders a game configuration or a solution as an image")
+                    .arg(Arg::with_name("file")
+                         .index(1)
+                         .required(true)
+                         .value_name("PUZZLE"))
+                    .arg(Arg::with_name("solution")
+                         .index(2)
+                         .value_name("SOLUTION"))
+                    .arg(Arg::with_name("format")
+                         .long("format")
+                         .short("f")
+                         .takes_value(true))
+                    .arg(Arg::with_name("output")
+                         .long("output")
+                         .short("o")
+                         .value_name("FILE")
+                         .takes_value(true))
+                    .arg(Arg::with_name("columns")
+                         .long("columns")
+                         .takes_value(true))
+                    .args(&style_args())).get_matches();
 
     if let Some(matches) = matches.subcommand_matches("solve") {
-        let heuristic = match matches.value_of("heuristic").unwrap_or("manhattan").to_lowercase().as_ref() {
-            "manhattan" => heuristics::eval_manhattan,
-            "euclide" => heuristics::eval_euclide,
-            "conflict" => heuristics::eval_conflict,
-            "misplaced" => heuristics::eval_misplaced,
-            h  => {
-                println!("Invalid value for heuritic: {}, possible values are: \
-                \n\tmanhattan: Manhattan distance \
-                \n\teuclide: Euclidean distance \
-                \n\tlinearconflict: Linear Conflict \
-                \n\tmisplaced: Misplaced tiles", h);
+        let name = matches.value_of("heuristic").unwrap_or("manhattan");
+        let heuristic = match heuristics::Heuristic::parse(name) {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("{}", heuristics::usage(name, &e));
                 return;
             }
         };
 
-        let file_name = match matches.value_of("file") {
-            Some(f) => f,
-            _ => {
-                println!("Missing map parameter");
+        let jobs: usize = matches.value_of("jobs")
+            .unwrap_or("1")
+            .parse()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for jobs: {},\
+                          using default value (1)", v);
+                1
+            });
+
+        let algorithm = match matches.value_of("algorithm").unwrap_or("astar").parse::<solver::Algorithm>() {
+            Ok(a) => a,
+            Err(e) => {
+                eprintln!("{}", e);
                 return;
             }
         };
-        let mut file = match File::open(file_name) {
+
+        let format = match matches.value_of("output").unwrap_or("grids").parse::<output::Format>() {
             Ok(f) => f,
             Err(e) => {
-                println!("Error: {}: {}", file_name, e);
+                eprintln!("{}", e);
                 return;
             }
         };
+        let tie_break = match matches.value_of("tie-break").unwrap_or("none").parse::<solver::open::TieBreak>() {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        let queue = match matches.value_of("open-list").unwrap_or("heap").parse::<solver::open::Queue>() {
+            Ok(q) => q,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+        let mem_budget: usize = matches.value_of("mem-budget")
+            .unwrap_or("1024")
+            .parse()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for mem-budget: {},\
+                          using default value (1024)", v);
+                1024
+            });
+        let external = solver::external::Config {
+            mem_budget: mem_budget * 1024 * 1024,
+            scratch_dir: matches.value_of("scratch-dir").map(PathBuf::from).unwrap_or_else(env::temp_dir),
+        };
+        let realtime = solver::realtime::Config {
+            nodes: matches.value_of("move-nodes")
+                .unwrap_or("100")
+                .parse()
+                .unwrap_or_else(|v| {
+                    eprintln!("#Warning: Invalid value provided for move-nodes: {},\
+                              using default value (100)", v);
+                    100
+                }),
+            time: matches.value_of("move-time").and_then(|v| match v.parse() {
+                Ok(ms) => Some(Duration::from_millis(ms)),
+                Err(e) => {
+                    eprintln!("#Warning: Invalid value provided for move-time: {},\
+                              using default value (no time limit)", e);
+                    None
+                }
+            }),
+            trials: matches.value_of("trials")
+                .unwrap_or("10")
+                .parse()
+                .unwrap_or_else(|v| {
+                    eprintln!("#Warning: Invalid value provided for trials: {},\
+                              using default value (10)", v);
+                    10
+                }),
+        };
+        let weight: f64 = matches.value_of("weight")
+            .unwrap_or("3")
+            .parse()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for weight: {},\
+                          using default value (3)", v);
+                3.0
+            });
+        let beam_width: usize = matches.value_of("beam-width")
+            .unwrap_or("1000")
+            .parse()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for beam-width: {},\
+                          using default value (1000)", v);
+                1000
+            });
+        let options = SolveOptions {
+            jobs,
+            tie_break,
+            queue,
+            algorithm,
+            format,
+            animate: if matches.is_present("animate") { Some(parse_delay(matches)) } else { None },
+            gif: matches.value_of("export-gif").map(|path| {
+                let delay = matches.value_of("frame-delay")
+                    .unwrap_or("300")
+                    .parse()
+                    .unwrap_or_else(|v| {
+                        eprintln!("#Warning: Invalid value provided for frame-delay: {},\
+                                  using default value (300)", v);
+                        300
+                    });
+                (path.to_string(), delay, matches.is_present("smooth"), parse_style(matches))
+            }),
+            dump_tree: matches.value_of("dump-tree").map(|path| {
+                let limit = matches.value_of("dump-limit")
+                    .unwrap_or("1000")
+                    .parse()
+                    .unwrap_or_else(|v| {
+                        eprintln!("#Warning: Invalid value provided for dump-limit: {},\
+                                  using default value (1000)", v);
+                        1000
+                    });
+                (path.to_string(), limit)
+            }),
+            trace: matches.value_of("trace").map(|path| path.to_string()),
+            external,
+            realtime,
+            weight,
+            beam_width,
+        };
 
-        let mut s = String::new();
-        file.read_to_string(&mut s).expect("Unable to read file");
+        if let Some(file_name) = matches.value_of("batch") {
+            let s = match read_file(file_name) {
+                Some(s) => s,
+                None => return,
+            };
+            for option in BATCH_IGNORED.iter().filter(|&&o| matches.is_present(o)) {
+                eprintln!("#Warning: --{} is not supported with --batch, ignoring it", option);
+            }
+            let results = batch::run(batch::parse(&s), &heuristic, jobs);
+            batch::print_summary(&results);
+            return;
+        }
+
+        for option in ignored_options(matches, &options.algorithm, jobs) {
+            let by = match options.algorithm {
+                solver::Algorithm::AStar if jobs > 1 => format!("--algorithm astar --jobs {}", jobs),
+                _ => format!("--algorithm {}", matches.value_of("algorithm").unwrap_or("astar")),
+            };
+            eprintln!("#Warning: --{} is not supported with {}, ignoring it", option, by);
+        }
+
+        let file_name = match matches.value_of("file") {
+            Some(f) => f,
+            _ => {
+                eprintln!("Missing map parameter");
+                return;
+            }
+        };
+        let s = match read_file(file_name) {
+            Some(s) => s,
+            None => return,
+        };
 
         match s.parse::<Node>() {
-            Ok(n) => solve(n, heuristic),
-            Err(e) => println!("Error: {}", e),
+            Ok(n) => solve(n, &heuristic, options),
+            Err(e) => eprintln!("Error: {}", e),
         }
     } else if let Some(matches) = matches.subcommand_matches("generate") {
         let size: usize = matches.value_of("size")
             .unwrap_or("3")
             .parse()
             .unwrap_or_else(|v| {
-                println!("#Warning: Invalid value provided for size: {},\
+                eprintln!("#Warning: Invalid value provided for size: {},\
                      usign default value (3)", v);
                 3
             });
@@ -89,7 +424,7 @@ fn main() {
             .unwrap_or("true")
             .parse()
             .unwrap_or_else(|v| {
-                println!("#Warning: Invalid value provided for solvable: {},\
+                eprintln!("#Warning: Invalid value provided for solvable: {},\
                          usign default value (true)", v);
                 true
             });
@@ -97,110 +432,614 @@ fn main() {
             .unwrap_or("10")
             .parse()
             .unwrap_or_else(|v| {
-                println!("#Warning: Invalid value provided for iterations: {},\
-                         using default value (10)", v);
+                eprintln!("#Warning: Invalid value provided for iterations: {},\
+                          using default value (10)", v);
                 10
             });
 
         let n = Node::random(size, iterations, solvable);
-        println!("{}", n.len);
-        let mut it = n.board.iter().peekable();
+        print_board(&n);
+    } else if let Some(matches) = matches.subcommand_matches("enumerate") {
+        let size: usize = matches.value_of("size")
+            .unwrap_or("3")
+            .parse()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for size: {},\
+                     usign default value (3)", v);
+                3
+            });
+        if !(2..=3).contains(&size) {
+            eprintln!("Error: exhaustive enumeration is only supported for sizes 2 and 3");
+            return;
+        }
+
+        let table = enumerate::DistanceTable::build(size);
+        println!("{} reachable states", table.reachable());
+        println!();
+        for (d, count) in table.histogram().iter().enumerate() {
+            println!("{0:>3} moves: {1}", d, count);
+        }
 
-        while it.peek().is_some() {
-            let line: String = it.by_ref()
-                                 .take(n.len)
-                                 .map(|v| format!("{0:<3}", v))
-                                 .collect::<Vec<_>>()
-                                 .join(" ");
-            println!("{}", line);
+        let hardest = table.hardest();
+        println!();
+        println!("{} hardest positions:", hardest.len());
+        for n in &hardest {
+            println!();
+            print_board(n);
         }
+
+        let output = matches.value_of("output")
+            .map(|o| o.to_string())
+            .unwrap_or_else(|| format!("distances_{}.bin", size));
+        match table.save(&output) {
+            Ok(()) => println!("\nDistance table written to {}", output),
+            Err(e) => eprintln!("Error: {}: {}", output, e),
+        }
+    } else if let Some(matches) = matches.subcommand_matches("check-heuristic") {
+        if !check_heuristics(matches) {
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("verify") {
+        if !verify_solution(matches) {
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("optimize") {
+        if !optimize_solution(matches) {
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("replay") {
+        if let Some((n, moves)) = read_solution(matches) {
+            match verify::boards(&n, &moves) {
+                Ok(boards) => replay::animate(&boards, n.len, parse_delay(matches)),
+                Err(e) => {
+                    eprintln!("Invalid solution: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("render") {
+        if !render(matches) {
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("play") {
+        let name = matches.value_of("heuristic").unwrap_or("manhattan");
+        let heuristic = match heuristics::Heuristic::parse(name) {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("{}", heuristics::usage(name, &e));
+                return;
+            }
+        };
+
+        let n = match matches.value_of("file") {
+            Some(file_name) => match read_file(file_name).map(|s| s.parse::<Node>()) {
+                Some(Ok(n)) => n,
+                Some(Err(e)) => {
+                    eprintln!("Error: {}", e);
+                    return;
+                }
+                None => return,
+            },
+            None => {
+                let size: usize = matches.value_of("size")
+                    .unwrap_or("3")
+                    .parse()
+                    .unwrap_or_else(|v| {
+                        eprintln!("#Warning: Invalid value provided for size: {},\
+                             usign default value (3)", v);
+                        3
+                    });
+                let iterations: usize = matches.value_of("iterations")
+                    .unwrap_or("100")
+                    .parse()
+                    .unwrap_or_else(|v| {
+                        eprintln!("#Warning: Invalid value provided for iterations: {},\
+                                  using default value (100)", v);
+                        100
+                    });
+                Node::random(size, iterations, true)
+            }
+        };
+
+        play::run(n, &heuristic);
     }
 }
 
-fn print_result(n: &Node) -> usize {
+fn style_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("tile-size")
+            .long("tile-size")
+            .takes_value(true),
+        Arg::with_name("highlight")
+            .long("highlight")
+            .value_name("COLOUR")
+            .min_values(0)
+            .takes_value(true),
+        Arg::with_name("background")
+            .long("background")
+            .value_name("COLOUR")
+            .takes_value(true),
+        Arg::with_name("tile-colour")
+            .long("tile-colour")
+            .value_name("COLOUR")
+            .takes_value(true),
+        Arg::with_name("text-colour")
+            .long("text-colour")
+            .value_name("COLOUR")
+            .takes_value(true),
+    ]
+}
+
+fn parse_style(matches: &clap::ArgMatches) -> render::Style {
+    let mut style = render::Style::default();
+    if let Some(size) = matches.value_of("tile-size") {
+        style.tile_size = size.parse::<usize>()
+            .unwrap_or_else(|v| {
+                eprintln!("#Warning: Invalid value provided for tile-size: {},\
+                          using default value (40)", v);
+                40
+            })
+            .max(8);
+    }
+    if matches.is_present("highlight") {
+        style.highlight = Some(matches.value_of("highlight").unwrap_or("#7fd77f").to_string());
+    }
+    if let Some(c) = matches.value_of("background") {
+        style.background = c.to_string();
+    }
+    if let Some(c) = matches.value_of("tile-colour") {
+        style.tile = c.to_string();
+    }
+    if let Some(c) = matches.value_of("text-colour") {
+        style.text = c.to_string();
+    }
+    style
+}
+
+fn render(matches: &clap::ArgMatches) -> bool {
+    if let Err(e) = matches.value_of("format").unwrap_or("svg").parse::<render::Format>() {
+        eprintln!("{}", e);
+        return false;
+    }
+    let (n, moves) = match read_solution(matches) {
+        Some(s) => s,
+        None => return false,
+    };
+    let boards = match verify::boards(&n, &moves) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Invalid solution: {}", e);
+            return false;
+        }
+    };
+
+    let style = parse_style(matches);
+    let columns = match matches.value_of("columns") {
+        Some(c) => c.parse().unwrap_or_else(|v| {
+            eprintln!("#Warning: Invalid value provided for columns: {},\
+                      using default value (a single row)", v);
+            boards.len()
+        }),
+        None => boards.len(),
+    };
+
+    let svg = render::svg(&boards, n.len, &style, columns);
+    match matches.value_of("output") {
+        Some(path) => match File::create(path).and_then(|mut f| f.write_all(svg.as_bytes())) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Error: {}: {}", path, e);
+                false
+            }
+        },
+        None => {
+            print!("{}", svg);
+            true
+        }
+    }
+}
+
+fn parse_delay(matches: &clap::ArgMatches) -> u64 {
+    matches.value_of("delay")
+        .unwrap_or("300")
+        .parse()
+        .unwrap_or_else(|v| {
+            eprintln!("#Warning: Invalid value provided for delay: {},\
+                      using default value (300)", v);
+            300
+        })
+}
+
+/// Reads the PUZZLE and SOLUTION arguments shared by `verify` and `replay`.
+fn read_solution(matches: &clap::ArgMatches) -> Option<(Node, Vec<verify::Move>)> {
+    let file_name = matches.value_of("file").unwrap_or("");
+    let n = match read_file(file_name).map(|s| s.parse::<Node>()) {
+        Some(Ok(n)) => n,
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            return None;
+        }
+        None => return None,
+    };
+
+    // The solution is read from a file when one exists, and taken literally otherwise
+    let solution = match matches.value_of("solution") {
+        Some(s) => s,
+        None => return Some((n, Vec::new())),
+    };
+    let s = match File::open(solution) {
+        Ok(_) => read_file(solution)?,
+        Err(_) => solution.to_string(),
+    };
+
+    match verify::parse(&s, &n) {
+        Ok(m) => Some((n, m)),
+        Err(e) => {
+            eprintln!("Invalid solution: {}", e);
+            None
+        }
+    }
+}
+
+/// Prints the shortened solution on stdout so it can be saved and checked
+/// again, and what was saved on stderr.
+fn optimize_solution(matches: &clap::ArgMatches) -> bool {
+    let (n, moves) = match read_solution(matches) {
+        Some(s) => s,
+        None => return false,
+    };
+    if let Err(e) = verify::verify(&n, &moves) {
+        eprintln!("Invalid solution: {}", e);
+        return false;
+    }
+
+    let window: usize = matches.value_of("window")
+        .unwrap_or("24")
+        .parse()
+        .unwrap_or_else(|v| {
+            eprintln!("#Warning: Invalid value provided for window: {},\
+                      using default value (24)", v);
+            24
+        });
+    let report = match optimize::optimize(&n, &moves, window) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Invalid solution: {}", e);
+            return false;
+        }
+    };
+
+    println!("{}", report.moves.iter().map(|m| m.to_string()).collect::<String>());
+    eprintln!("Saved {} moves ({} -> {}): {} undone by the next move, {} by shorter sub-paths",
+              moves.len() - report.moves.len(), moves.len(), report.moves.len(), report.inverse, report.shortened);
+    true
+}
+
+fn verify_solution(matches: &clap::ArgMatches) -> bool {
+    let (n, moves) = match read_solution(matches) {
+        Some(s) => s,
+        None => return false,
+    };
+
+    if let Err(e) = verify::verify(&n, &moves) {
+        println!("Invalid solution: {}", e);
+        if let verify::VerifyError::NotSolved(end) = e {
+            print_board(&end);
+        }
+        return false;
+    }
+    println!("Valid solution in {} moves", moves.len());
+
+    // The shortest solution is found by a bidirectional breadth-first search,
+    // which only finishes in reasonable time and memory up to 3x3.
+    if matches.is_present("optimal") && n.len > 3 {
+        eprintln!("Error: --optimal is only supported up to 3x3 boards");
+        return false;
+    }
+    if matches.is_present("optimal") {
+        match solver::bidirectional::search(n) {
+            Some((best, _)) if best.cost == moves.len() => println!("Solution is optimal"),
+            Some((best, _)) => {
+                println!("Solution is not optimal, the shortest one has {} moves", best.cost);
+                return false;
+            }
+            None => {
+                eprintln!("Error: no solution found to compare with");
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn check_heuristics(matches: &clap::ArgMatches) -> bool {
+    let names = match matches.value_of("heuristic") {
+        Some(name) => vec![name],
+        None => heuristics::NAMES.to_vec(),
+    };
+    let size: usize = matches.value_of("size")
+        .unwrap_or("3")
+        .parse()
+        .unwrap_or_else(|v| {
+            eprintln!("#Warning: Invalid value provided for size: {},\
+                 usign default value (3)", v);
+            3
+        });
+
+    let table = match matches.value_of("table") {
+        Some(path) => match enumerate::DistanceTable::load(path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Error: {}: {}", path, e);
+                return false;
+            }
+        },
+        None if (2..=3).contains(&size) => enumerate::DistanceTable::build(size),
+        None => {
+            eprintln!("Error: exact distances are only available for sizes 2 and 3");
+            return false;
+        }
+    };
+
+    let mut sound = true;
+    for name in names {
+        let h = match heuristics::Heuristic::parse(name) {
+            Ok(h) => h,
+            Err(e) => {
+                eprintln!("{}", heuristics::usage(name, &e));
+                return false;
+            }
+        };
+        let report = heuristics::check::check(&h, &table);
+
+        println!("{} ({} states, average gap to true distance: {:.2})", name, report.states, report.mean_gap);
+        match report.worst_overestimate {
+            None => println!("\tadmissible"),
+            Some((ref board, h, d)) => println!("\tNOT admissible: {} overestimates, worst {:?} has h = {} for a distance of {}",
+                                                report.overestimates, board, h, d),
+        }
+        match report.worst_inconsistency {
+            None => println!("\tconsistent"),
+            Some((ref from, ref to, hf, ht)) => println!("\tNOT consistent: {} violations, worst {:?} (h = {}) -> {:?} (h = {})",
+                                                          report.inconsistencies, from, hf, to, ht),
+        }
+        if !report.is_informative() {
+            println!("\tNOT informative: average h of {:.2} for an average distance of {:.2}",
+                     report.mean_distance - report.mean_gap, report.mean_distance);
+        }
+        sound = sound && report.is_admissible() && report.is_consistent();
+    }
+
+    sound
+}
+
+fn print_board(n: &Node) {
+    println!("{}", n.len);
+    let mut it = n.board.iter().peekable();
+
+    while it.peek().is_some() {
+        let line: String = it.by_ref()
+                             .take(n.len)
+                             .map(|v| format!("{0:<3}", v))
+                             .collect::<Vec<_>>()
+                             .join(" ");
+        println!("{}", line);
+    }
+}
+
+fn read_file(file_name: &str) -> Option<String> {
+    let mut file = match File::open(file_name) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: {}: {}", file_name, e);
+            return None;
+        }
+    };
+
+    let mut s = String::new();
+    file.read_to_string(&mut s).expect("Unable to read file");
+    Some(s)
+}
+
+fn print_result(n: &Node, colours: bool) -> usize {
     let v = n.parents().collect::<Vec<_>>();
     let mut it = v.iter().rev().map(|node| node.board.clone());
     let v2 = n.parents().collect::<Vec<_>>();
     let it2 = v2.iter().rev().map(|node| node.board.clone());
 
 
+    let paint = |c: ansi_term::Colour, s: String| {
+        if colours {
+            c.paint(s).to_string()
+        } else {
+            s
+        }
+    };
+
     let first_node = it.next().unwrap();
     let len = n.len;
     let solution_len = v.len();
     for x in 0..len {
         let line = &first_node[x * len..x * len + len];
         for n in line {
-            print!("{} ", ansi_term::Colour::White.paint(format!("{0:<3}", n)));
+            print!("{} ", paint(ansi_term::Colour::White, format!("{0:<3}", n)));
         }
-        println!("");
+        println!();
     }
-    println!("");
+    println!();
 
     for (b1, b2) in it.zip(it2) {
         let colours = Node::format_colors(&b1, &b2);
         for x in 0..len {
             let colored_numbers = colours[x * len..x * len + len].iter().map(|&(c, v)| {
-                c.paint(format!("{0:<3}", v.to_string())).to_string()
+                paint(c, format!("{0:<3}", v.to_string()))
             }).collect::<Vec<_>>().join(" ");
             println!("{}", colored_numbers);
         }
-        println!("");
+        println!();
     }
     println!("Solved in {} moves!", solution_len - 1);
 
     solution_len
 }
 
-pub fn solve(n: Node, h: fn(&node::Node) -> usize) {
-    let goal: Node = Node::goal(n.len);
+pub struct SolveOptions {
+    jobs: usize,
+    tie_break: solver::open::TieBreak,
+    queue: solver::open::Queue,
+    algorithm: solver::Algorithm,
+    format: output::Format,
+    /// Delay between frames when the solution is animated.
+    animate: Option<u64>,
+    /// Path, frame delay, sliding frames and style of the GIF export.
+    gif: Option<(String, u64, bool, render::Style)>,
+    /// Path and expansion limit of the search tree dump.
+    dump_tree: Option<(String, usize)>,
+    /// Path of the per-expansion trace log.
+    trace: Option<String>,
+    external: solver::external::Config,
+    realtime: solver::realtime::Config,
+    /// Weight of the heuristic for the anytime search.
+    weight: f64,
+    beam_width: usize,
+}
+
+/// Options of `solve` that batch mode does not honour: every board is solved
+/// with sequential A* and only a summary is printed.
+const BATCH_IGNORED: &[&str] = &[
+    "algorithm", "output", "animate", "delay", "export-gif", "frame-delay", "smooth",
+    "dump-tree", "dump-limit", "trace", "tie-break", "open-list", "mem-budget", "scratch-dir",
+    "move-nodes", "move-time", "trials", "weight", "beam-width",
+];
+
+/// Options given on the command line that `algorithm` does not honour.
+fn ignored_options(matches: &clap::ArgMatches, algorithm: &solver::Algorithm, jobs: usize) -> Vec<&'static str> {
+    use solver::Algorithm::*;
+
+    let sequential = matches!(algorithm, AStar) && jobs == 1;
+    let honoured = [
+        ("jobs", matches!(algorithm, AStar)),
+        ("dump-tree", sequential),
+        ("dump-limit", sequential),
+        ("trace", sequential),
+        ("tie-break", sequential),
+        ("open-list", sequential),
+        ("mem-budget", matches!(algorithm, External)),
+        ("scratch-dir", matches!(algorithm, External)),
+        ("move-nodes", matches!(algorithm, Lrta)),
+        ("move-time", matches!(algorithm, Lrta)),
+        ("trials", matches!(algorithm, Lrta)),
+        ("weight", matches!(algorithm, Anytime)),
+        ("beam-width", matches!(algorithm, Beam)),
+    ];
 
-    if n == goal {
+    honoured.iter()
+        .filter(|&&(name, honoured)| !honoured && matches.is_present(name))
+        .map(|&(name, _)| name)
+        .collect()
+}
+
+pub fn solve(n: Node, h: &heuristics::Heuristic, options: SolveOptions) {
+    let SolveOptions { jobs, tie_break, queue, algorithm, format, animate, gif, dump_tree, trace, external, realtime, weight, beam_width } = options;
+    let observed = dump_tree.is_some() || trace.is_some() || tie_break != solver::open::TieBreak::Arbitrary
+        || queue != solver::open::Queue::Heap;
+    if format == output::Format::Grids && n == Node::goal(n.len) {
         println!("Puzzle is already solved");
         return;
     }
 
-    let r = Rc::new(n);
-    let mut open: BinaryHeap<Rc<Node>> = BinaryHeap::new();
-    let mut opened: HashSet<Rc<Node>> = HashSet::new();
-    let mut closed: HashSet<Rc<Node>> = HashSet::new();
-
-    opened.insert(r.clone());
-    open.push(r);
-    let mut total_states = 1_usize;
-    let mut max_states = 0_usize;
-
-    while let Some(node) = open.pop() {
-        let sum = opened.len();
-        if sum > max_states {
-            max_states = sum;
+    let result = match algorithm {
+        solver::Algorithm::Bidirectional => solver::bidirectional::search(n),
+        solver::Algorithm::Frontier => solver::frontier::astar(n, h),
+        solver::Algorithm::Constructive => solver::constructive::solve(n, h),
+        solver::Algorithm::Beam => {
+            let lower = heuristics::eval_tiles(&n);
+            let result = solver::beam::search(n, h, beam_width);
+            if let (Some((node, _)), output::Format::Grids) = (result.as_ref(), format) {
+                println!("Solution of {} moves, {:.2} times the lower bound of {} moves",
+                         node.cost, node.cost as f64 / lower.max(1) as f64, lower);
+            }
+            result
         }
-        if *(node.as_ref()) == goal {
-            print_result(node.as_ref());
-            println!("{} states ever selected in open set", total_states);
-            println!("{} states represented in memory at the same time", max_states);
-            break
-        } else {
-            let r = node;
-            let neighbours = Node::get_next_steps(&r, h);
+        solver::Algorithm::Anytime => {
+            let interrupt = term::Interrupt::catch();
+            solver::anytime::search(n, h, weight, interrupt.flag(), &mut |node, lower| {
+                eprintln!("#Solution of {} moves, optimal is at least {}: {}", node.cost, lower, output::moves_string(node));
+            })
+        }
+        solver::Algorithm::Lrta => {
+            let (result, curve) = solver::realtime::lrta(n, h, &realtime);
+            if format == output::Format::Grids {
+                for (i, moves) in curve.iter().enumerate() {
+                    println!("Trial {}: {} moves", i + 1, moves);
+                }
+            }
+            result
+        }
+        solver::Algorithm::External => match solver::external::search(n, h, &external) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Error: {}: {}", external.scratch_dir.display(), e);
+                return;
+            }
+        },
+        solver::Algorithm::AStar if jobs > 1 => solver::hda::astar(n, h, jobs),
+        solver::Algorithm::AStar if observed => {
+            let dump = dump_tree.as_ref().map(|&(_, limit)| output::dot::TreeDump::new(limit));
+            let log = match trace.as_ref().map(|path| output::trace::Trace::create(path)) {
+                Some(Err(e)) => {
+                    eprintln!("Error: {}: {}", trace.unwrap_or_default(), e);
+                    return;
+                }
+                Some(Ok(t)) => Some(t),
+                None => None,
+            };
 
-            for neighbour in neighbours {
-                if closed.get(&neighbour).is_some() {
-                    continue;
+            let mut observers = (dump, log);
+            let result = solver::astar_observed(n, h, solver::open::build(queue, tie_break), &mut observers);
+            if let (Some(dump), Some((path, _))) = (observers.0, dump_tree.as_ref()) {
+                if let Err(e) = dump.write(path, result.as_ref().map(|r| r.0.as_ref())) {
+                    eprintln!("Error: {}: {}", path, e);
                 }
+            }
+            if let (Some(log), Some(path)) = (observers.1, trace.as_ref()) {
+                if let Err(e) = log.finish() {
+                    eprintln!("Error: {}: {}", path, e);
+                }
+            }
+            result
+        }
+        solver::Algorithm::AStar => solver::astar(n, h),
+    };
 
-                let should_push = opened.get(&neighbour)
-                    .map(|node| node.cost > neighbour.cost)
-                    .unwrap_or(true);
+    if let Some((node, stats)) = result {
+        let boards = output::path(&node).iter().map(|n| n.board.clone()).collect::<Vec<_>>();
+        if let Some(delay) = animate {
+            replay::animate(&boards, node.len, delay);
+        }
+        if let Some((path, delay, smooth, style)) = gif {
+            if let Err(e) = render::export_gif(&path, &boards, node.len, &style, delay, smooth) {
+                eprintln!("Error: {}: {}", path, e);
+            }
+        }
 
-                if should_push {
-                    total_states += 1;
-                    let rc = Rc::new(neighbour);
-                    opened.insert(rc.clone());
-                    open.push(rc.clone());
+        match format {
+            output::Format::Grids => {
+                if animate.is_some() {
+                    println!("Solved in {} moves!", node.cost);
+                } else {
+                    print_result(node.as_ref(), io::stdout().is_terminal());
                 }
+                println!("{} states ever selected in open set", stats.total_states);
+                println!("{} states represented in memory at the same time", stats.max_states);
             }
-            opened.remove(r.as_ref());
-            closed.insert(r);
+            output::Format::Moves => println!("{}", output::moves_string(&node)),
+            output::Format::Json => println!("{}", output::json(&node, &stats)),
+            output::Format::Csv => println!("{}", output::csv(&node)),
         }
+    } else {
+        eprintln!("No solution found");
+        std::process::exit(1);
     }
 }
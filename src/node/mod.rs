@@ -5,11 +5,13 @@ use rand;
 use rand::Rng;
 use std::collections::HashMap;
 use ansi_term::Colour;
-use std::rc::Rc;
+use heuristics::Heuristic;
+use std::sync::Arc;
 use std::hash::{Hash, Hasher};
 
 type Board = Vec<usize>;
 
+#[derive (Clone, Copy, PartialEq, Debug)]
 pub enum Direction {
     North,
     South,
@@ -17,13 +19,24 @@ pub enum Direction {
     West,
 }
 
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
 #[derive (Clone)]
 pub struct Node {
     pub board: Board,
     pub len: usize,
     pub cost: usize,
     pub heuristic: usize,
-    pub parents: Option<Rc<Node>>,
+    pub parents: Option<Arc<Node>>,
 }
 
 impl Hash for Node {
@@ -40,19 +53,8 @@ impl<'a> Iterator for NodeIter<'a> {
     type Item = &'a Node;
 
     fn next(&mut self) -> Option<&'a Node> {
-        let res = match self.current {
-            None => None,
-            Some(n) => Some(n),
-        };
-        match res {
-            None => self.current = None,
-            Some(r) => {
-                self.current = match r.parents {
-                    Some(ref r) => Some(r),
-                    None => None,
-                }
-            }
-        }
+        let res = self.current;
+        self.current = res.and_then(|r| r.parents.as_ref().map(|p| p.as_ref()));
         res
     }
 }
@@ -106,6 +108,7 @@ impl PartialOrd for Node {
 }
 
 #[derive (Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum NodeError {
     ParseError,
     InvalidContentError,
@@ -149,9 +152,9 @@ impl FromStr for Node {
             }
         });
 
-        let len = try!(lines.next()
+        let len = lines.next()
                        .ok_or(NodeError::ParseError)
-                       .and_then(|line| line.parse::<usize>().map_err(|_| NodeError::ParseError)));
+                       .and_then(|line| line.parse::<usize>().map_err(|_| NodeError::ParseError))?;
         if len == 0 {
             return Err(NodeError::ParseError)
         }
@@ -177,7 +180,7 @@ impl FromStr for Node {
 
         let node = Node {
             board: values,
-            len: len,
+            len,
             cost: 0,
             heuristic: 0,
             parents: None,
@@ -231,7 +234,7 @@ impl Node {
         let goal = Node::goal(self.len);
         let mut goal_invs = inversions(goal.board.as_slice());
         let mut self_invs = inversions(self.board.as_slice());
-        if self.len % 2 == 0 {
+        if self.len.is_multiple_of(2) {
             self_invs += self.board.iter().position(|&e| e == 0).unwrap_or(0) / self.len;
             goal_invs += goal.board.iter().position(|&e| e == 0).unwrap_or(0) / self.len;
         }
@@ -252,13 +255,8 @@ impl Node {
         }
 
         for i in 0..self.len * self.len {
-            match occurences.get(&i) {
-                None => {} // should never happen
-                Some(x) => {
-                    if *x == 0 {
-                        return false;
-                    }
-                }
+            if occurences.get(&i) == Some(&0) {
+                return false;
             }
         }
 
@@ -319,11 +317,8 @@ impl Node {
 
     pub fn make_unsolvable(&mut self) {
         match self.len {
-            0 => return,
-            1 => {
-                self.board[0] = 2;
-                return;
-            }
+            0 => {}
+            1 => self.board[0] = 2,
             _ => {
 
                 let (idx1, idx2) = if self.board[0] == 0 || self.board[1] == 0 {
@@ -363,13 +358,10 @@ impl Node {
     }
 
     pub fn get_pos(&self, num: usize) -> Option<(usize, usize)> {
-        match self.board.iter().position(|&r| r == num) {
-            Some(pos) => Some((pos / self.len, pos % self.len)),
-            None => None,
-        }
+        self.board.iter().position(|&r| r == num).map(|pos| (pos / self.len, pos % self.len))
     }
 
-    fn permute(direction: Direction, h: fn(&Node) -> usize, n: &Rc<Node>) -> Node {
+    fn permute(direction: Direction, h: &Heuristic, n: &Arc<Node>) -> Node {
         let (x, y) = n.get_pos(0).unwrap();
 
         let (new_x, new_y) = match direction {
@@ -395,13 +387,13 @@ impl Node {
         Node {
             board: new_board,
             len: n.len,
-            heuristic: h(&tmp_node),
+            heuristic: h.eval(&tmp_node),
             cost: n.cost + 1,
             parents: Some(n.clone()),
         }
     }
 
-    pub fn get_next_steps(n: &Rc<Node>, h: fn(&Node) -> usize) -> Vec<Node> {
+    pub fn get_next_steps(n: &Arc<Node>, h: &Heuristic) -> Vec<Node> {
         let (x, y) = n.get_pos(0).unwrap();
 
         let mut next_states: Vec<Node> = Vec::with_capacity(4);
@@ -425,7 +417,7 @@ impl Node {
         next_states
     }
 
-    pub fn parents(&self) -> NodeIter {
+    pub fn parents(&self) -> NodeIter<'_> {
         NodeIter {
             current: Some(self),
         }
@@ -435,19 +427,19 @@ impl Node {
 #[cfg(test)]
 mod test {
     use super::Node;
-    use super::heuristics;
+    use heuristics::Heuristic;
     use super::Direction;
-    use std::rc::Rc;
+    use std::sync::Arc;
     #[test]
     fn test_permute() {
-        let h = heuristics::Manhattan;
+        let h = Heuristic::parse("manhattan").unwrap();
         let n: Node = "
 3
 1   2   3
 8   0   4
 7   6   5
 ".parse().expect("Map should be valid");
-        let r = Rc::new(n);
+        let r = Arc::new(n);
         let south = Node::permute(Direction::South, &h, &r);
         assert!(south.board == vec![1, 2, 3, 8, 6, 4, 7, 0, 5]);
         let north = Node::permute(Direction::North, &h, &r);
@@ -0,0 +1,232 @@
+//! Post-optimizer for solutions from the non-optimal solvers. Moves undone by
+//! the next one are cancelled, then every window of the path is replaced by
+//! an optimal path between the same two boards when a shorter one exists.
+//! The window search is an IDA* on the Manhattan distance of the tiles to
+//! the board at the end of the window, capped in expansions so that long
+//! solutions on large boards are still handled quickly.
+
+use node::{Direction, Node};
+use std::cmp;
+use verify::{self, Move, VerifyError};
+
+/// Expansions allowed to the search of one window.
+const WINDOW_BUDGET: usize = 100_000;
+
+pub struct Report {
+    pub moves: Vec<Move>,
+    /// Moves removed because the next move undid them.
+    pub inverse: usize,
+    /// Moves removed by replacing windows with shorter paths.
+    pub shortened: usize,
+}
+
+/// Cells of the blank along the solution, start included.
+fn blank_path(start: &Node, moves: &[Move]) -> Result<Vec<usize>, VerifyError> {
+    let mut board = start.board.clone();
+    let blank = |b: &[usize]| b.iter().position(|&v| v == 0).unwrap_or(0);
+    let mut path = vec![blank(&board)];
+    for (i, &m) in moves.iter().enumerate() {
+        verify::apply(&mut board, start.len, m).map_err(|e| VerifyError::IllegalMove(i, e))?;
+        path.push(blank(&board));
+    }
+    Ok(path)
+}
+
+/// Drops every blank move that the next one takes back, cascading.
+fn cancel_inverse(path: &[usize]) -> Vec<usize> {
+    let mut res: Vec<usize> = Vec::with_capacity(path.len());
+    for &p in path {
+        if res.len() >= 2 && res[res.len() - 2] == p {
+            res.pop();
+        } else {
+            res.push(p);
+        }
+    }
+    res
+}
+
+struct Ida {
+    board: Vec<usize>,
+    len: usize,
+    /// Cell of every tile in the board to reach.
+    target: Vec<usize>,
+    path: Vec<usize>,
+    budget: usize,
+}
+
+impl Ida {
+    fn distance(&self, tile: usize, cell: usize) -> usize {
+        let goal = self.target[tile];
+        (cell / self.len).abs_diff(goal / self.len) + (cell % self.len).abs_diff(goal % self.len)
+    }
+
+    fn neighbours(&self, i: usize) -> Vec<usize> {
+        let (x, y) = (i / self.len, i % self.len);
+        let mut res = Vec::with_capacity(4);
+        if x > 0 {
+            res.push(i - self.len);
+        }
+        if x + 1 < self.len {
+            res.push(i + self.len);
+        }
+        if y > 0 {
+            res.push(i - 1);
+        }
+        if y + 1 < self.len {
+            res.push(i + 1);
+        }
+        res
+    }
+
+    /// None when the target is reached, otherwise the smallest f above the
+    /// bound, `usize::MAX` once the budget is spent.
+    fn search(&mut self, blank: usize, previous: usize, g: usize, h: usize, bound: usize) -> Option<usize> {
+        if g + h > bound {
+            return Some(g + h);
+        }
+        if h == 0 {
+            return None;
+        }
+        if self.budget == 0 {
+            return Some(usize::MAX);
+        }
+        self.budget -= 1;
+
+        let mut next_bound = usize::MAX;
+        for next in self.neighbours(blank) {
+            if next == previous {
+                continue;
+            }
+            let tile = self.board[next];
+            let h = h + self.distance(tile, blank) - self.distance(tile, next);
+            self.board.swap(blank, next);
+            self.path.push(next);
+            match self.search(next, blank, g + 1, h, bound) {
+                None => return None,
+                Some(b) => next_bound = cmp::min(next_bound, b),
+            }
+            self.path.pop();
+            self.board.swap(blank, next);
+        }
+        Some(next_bound)
+    }
+}
+
+/// Blank cells of a shortest path from `from` to `to` of at most `limit`
+/// moves, start excluded. None as well once `budget` expansions are spent.
+pub fn shortest(from: &[usize], blank: usize, to: &[usize], len: usize, limit: usize, budget: usize) -> Option<Vec<usize>> {
+    let mut target = vec![0; to.len()];
+    for (i, &v) in to.iter().enumerate() {
+        target[v] = i;
+    }
+    let mut ida = Ida {
+        board: from.to_vec(),
+        len,
+        target,
+        path: Vec::new(),
+        budget,
+    };
+    let h = from.iter().enumerate()
+        .filter(|&(_, &v)| v != 0)
+        .map(|(i, &v)| ida.distance(v, i))
+        .sum();
+
+    let mut bound = h;
+    while bound <= limit {
+        match ida.search(blank, usize::MAX, 0, h, bound) {
+            None => return Some(ida.path),
+            Some(b) => bound = b,
+        }
+    }
+    None
+}
+
+/// Replaces windows of `window` moves with shorter paths, returns the number
+/// of moves saved.
+fn shorten(start: &[usize], len: usize, path: &mut Vec<usize>, window: usize) -> usize {
+    let mut board = start.to_vec();
+    let mut saved = 0;
+    let mut i = 0;
+    while i + 2 < path.len() {
+        let j = cmp::min(i + window, path.len() - 1);
+        let mut target = board.clone();
+        for k in i + 1..=j {
+            target.swap(path[k - 1], path[k]);
+        }
+        if let Some(sub) = shortest(&board, path[i], &target, len, j - i - 1, WINDOW_BUDGET) {
+            saved += j - i - sub.len();
+            path.splice(i + 1..=j, sub);
+            if i + 1 == path.len() {
+                break;
+            }
+        }
+
+        board.swap(path[i], path[i + 1]);
+        i += 1;
+    }
+    saved
+}
+
+/// Direction the blank takes from cell `from` to the neighbouring `to`.
+pub fn direction(from: usize, to: usize, len: usize) -> Direction {
+    if to + len == from {
+        Direction::North
+    } else if to == from + len {
+        Direction::South
+    } else if to + 1 == from {
+        Direction::West
+    } else {
+        Direction::East
+    }
+}
+
+/// Shortens `moves`, played from `start`, with windows of `window` moves.
+pub fn optimize(start: &Node, moves: &[Move], window: usize) -> Result<Report, VerifyError> {
+    let original = blank_path(start, moves)?;
+    let mut path = cancel_inverse(&original);
+    let mut inverse = original.len() - path.len();
+    let mut shortened = 0;
+
+    loop {
+        let saved = shorten(&start.board, start.len, &mut path, cmp::max(window, 2));
+        let before = path.len();
+        path = cancel_inverse(&path);
+        shortened += saved;
+        inverse += before - path.len();
+        if saved == 0 && before == path.len() {
+            break;
+        }
+    }
+
+    let moves = path.windows(2)
+        .map(|w| Move::Blank(direction(w[0], w[1], start.len)))
+        .collect();
+    Ok(Report { moves, inverse, shortened })
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::Heuristic;
+    use node::Node;
+    use output;
+    use solver;
+    use verify;
+
+    #[test]
+    fn optimize_removes_waste() {
+        // Back and forth, then three times round a 2x2 square: no move is needed
+        let n: Node = "3\n1 2 3\n8 0 4\n7 6 5\n".parse().expect("Map should be valid");
+        let moves = verify::parse("UDLRRDLURDLURDLU", &n).ok().unwrap();
+        let report = super::optimize(&n, &moves, 12).ok().unwrap();
+        assert!(report.moves.is_empty());
+        assert!(report.inverse == 4 && report.shortened == 12);
+
+        let start: Node = "3\n2 8 3\n1 6 4\n7 0 5\n".parse().expect("Map should be valid");
+        let (best, _) = solver::astar(start.clone(), &Heuristic::parse("tiles").unwrap()).unwrap();
+        let long = "URDLURDLURDL".to_string() + &output::moves_string(&best);
+        let moves = verify::parse(&long, &start).ok().unwrap();
+        let report = super::optimize(&start, &moves, 12).ok().unwrap();
+        assert!(verify::verify(&start, &report.moves).is_ok());
+        assert!(report.moves.len() == best.cost);
+    }
+}
@@ -0,0 +1,159 @@
+//! Graphviz export of the A* search tree.
+
+use node::Node;
+use output;
+use solver::{Observer, Outcome};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+struct Vertex {
+    label: String,
+    expanded: bool,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    label: String,
+    outcome: Outcome,
+}
+
+/// Records expansions until `limit` nodes have been expanded.
+pub struct TreeDump {
+    limit: usize,
+    expanded: usize,
+    ids: HashMap<Vec<usize>, usize>,
+    vertices: Vec<Vertex>,
+    edges: Vec<Edge>,
+}
+
+fn label(n: &Node) -> String {
+    let rows = n.board.chunks(n.len)
+        .map(|r| r.iter().map(|v| if *v == 0 { " ".to_string() } else { v.to_string() }).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\\n");
+    format!("{}\\ng={} h={} f={}", rows, n.cost, n.heuristic, n.cost + n.heuristic)
+}
+
+impl TreeDump {
+    pub fn new(limit: usize) -> TreeDump {
+        TreeDump {
+            limit,
+            expanded: 0,
+            ids: HashMap::new(),
+            vertices: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn id(&mut self, n: &Node) -> usize {
+        if let Some(&id) = self.ids.get(&n.board) {
+            return id;
+        }
+        let id = self.vertices.len();
+        self.ids.insert(n.board.clone(), id);
+        self.vertices.push(Vertex {
+            label: label(n),
+            expanded: false,
+        });
+        id
+    }
+
+    /// Writes the graph, drawing the path to `solution` in bold red.
+    pub fn write(&self, path: &str, solution: Option<&Node>) -> io::Result<()> {
+        let mut on_path: HashSet<usize> = HashSet::new();
+        let mut path_edges: HashSet<(usize, usize)> = HashSet::new();
+        if let Some(end) = solution {
+            let p = output::path(end);
+            for w in p.windows(2) {
+                if let (Some(&a), Some(&b)) = (self.ids.get(&w[0].board), self.ids.get(&w[1].board)) {
+                    path_edges.insert((a, b));
+                }
+            }
+            on_path.extend(p.iter().filter_map(|n| self.ids.get(&n.board)));
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "digraph search {{")?;
+        writeln!(out, "  node [shape=box, fontname=\"monospace\"];")?;
+        for (id, v) in self.vertices.iter().enumerate() {
+            let mut attrs = vec![format!("label=\"{}\"", v.label)];
+            if !v.expanded {
+                attrs.push("style=dashed".to_string());
+            }
+            if on_path.contains(&id) {
+                attrs.push("color=red, penwidth=2".to_string());
+            }
+            writeln!(out, "  n{} [{}];", id, attrs.join(", "))?;
+        }
+        for e in &self.edges {
+            let mut attrs = vec![format!("label=\"{}\"", e.label)];
+            match e.outcome {
+                Outcome::Pushed => {}
+                Outcome::Reopened => attrs.push("color=orange, xlabel=\"reopened\"".to_string()),
+                Outcome::Closed => attrs.push("style=dotted, color=gray, xlabel=\"closed\"".to_string()),
+                Outcome::Opened => attrs.push("style=dotted, color=blue, xlabel=\"open\"".to_string()),
+            }
+            if path_edges.contains(&(e.from, e.to)) {
+                attrs.push("color=red, penwidth=2".to_string());
+            }
+            writeln!(out, "  n{} -> n{} [{}];", e.from, e.to, attrs.join(", "))?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+impl Observer for TreeDump {
+    fn expand(&mut self, node: &Node, _open: usize, _closed: usize) {
+        if self.expanded >= self.limit {
+            return;
+        }
+        self.expanded += 1;
+        let id = self.id(node);
+        self.vertices[id].expanded = true;
+    }
+
+    fn generate(&mut self, parent: &Node, child: &Node, outcome: Outcome) {
+        // Only children of recorded expansions are kept
+        let from = match self.ids.get(&parent.board) {
+            Some(&id) if self.vertices[id].expanded => id,
+            _ => return,
+        };
+        let to = self.id(child);
+        self.edges.push(Edge {
+            from,
+            to,
+            label: output::blank_move(parent, child).to_string(),
+            outcome,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TreeDump;
+    use heuristics::Heuristic;
+    use node::Node;
+    use solver;
+    use solver::open::{self, Queue, TieBreak};
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn dot_dump_test() {
+        let n: Node = "3\n1 2 3\n0 8 4\n7 6 5\n".parse().expect("Map should be valid");
+        let mut dump = TreeDump::new(10);
+        let (end, _) = solver::astar_observed(n, &Heuristic::parse("manhattan").unwrap(), open::build(Queue::Heap, TieBreak::Arbitrary), &mut dump).unwrap();
+
+        let path = ::std::env::temp_dir().join("npuzzle_dot_dump_test.dot");
+        let path = path.to_str().unwrap();
+        dump.write(path, Some(&end)).unwrap();
+        let mut s = String::new();
+        File::open(path).unwrap().read_to_string(&mut s).unwrap();
+
+        assert!(s.starts_with("digraph search {"));
+        assert!(s.contains("g=0 h="));
+        assert!(s.contains("[label=\"R\", color=red, penwidth=2]"));
+    }
+}
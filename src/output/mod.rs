@@ -0,0 +1,113 @@
+//! Machine-readable renderings of a solution, next to the coloured grids of
+//! `print_result`.
+
+pub mod dot;
+pub mod trace;
+
+use node::{Direction, Node};
+use solver::Stats;
+use std::str::FromStr;
+use verify::Move;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Moves,
+    Grids,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "moves" => Ok(Format::Moves),
+            "grids" => Ok(Format::Grids),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            o => Err(format!("Invalid value for output: {}, possible values are: \
+                 \n\tmoves: Blank moves as a UDLR string \
+                 \n\tgrids: Every intermediate board \
+                 \n\tjson: Boards, moves and statistics as JSON \
+                 \n\tcsv: One board per row", o)),
+        }
+    }
+}
+
+/// Boards of the solution, from the starting position to the goal.
+pub fn path(n: &Node) -> Vec<&Node> {
+    let mut v = n.parents().collect::<Vec<_>>();
+    v.reverse();
+    v
+}
+
+/// Direction the blank travels between two consecutive boards.
+pub fn blank_move(from: &Node, to: &Node) -> Move {
+    let a = from.board.iter().position(|&v| v == 0).unwrap_or(0);
+    let b = to.board.iter().position(|&v| v == 0).unwrap_or(0);
+
+    Move::Blank(if b + from.len == a {
+        Direction::North
+    } else if b == a + from.len {
+        Direction::South
+    } else if b + 1 == a {
+        Direction::West
+    } else {
+        Direction::East
+    })
+}
+
+pub fn moves(n: &Node) -> Vec<Move> {
+    path(n).windows(2).map(|w| blank_move(w[0], w[1])).collect()
+}
+
+fn join(board: &[usize], sep: &str) -> String {
+    board.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(sep)
+}
+
+pub fn moves_string(n: &Node) -> String {
+    moves(n).iter().map(|m| m.to_string()).collect()
+}
+
+pub fn json(n: &Node, stats: &Stats) -> String {
+    let boards = path(n).iter()
+        .map(|b| format!("[{}]", join(&b.board, ",")))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("{{\"size\":{},\"length\":{},\"moves\":\"{}\",\"boards\":[{}],\"states_selected\":{},\"max_states\":{}}}",
+            n.len, n.cost, moves_string(n), boards, stats.total_states, stats.max_states)
+}
+
+pub fn csv(n: &Node) -> String {
+    let p = path(n);
+    let header = (0..n.len * n.len).map(|i| format!("t{}", i)).collect::<Vec<_>>().join(",");
+    let mut lines = vec![format!("step,move,{}", header)];
+
+    for (i, b) in p.iter().enumerate() {
+        let m = if i == 0 { String::new() } else { blank_move(p[i - 1], b).to_string() };
+        lines.push(format!("{},{},{}", i, m, join(&b.board, ",")));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{csv, json, moves_string};
+    use node::Node;
+    use solver::{self, Stats};
+    use heuristics::Heuristic;
+
+    #[test]
+    fn output_formats_test() {
+        let n: Node = "3\n1 2 3\n8 4 0\n7 6 5\n".parse().expect("Map should be valid");
+        let (end, stats) = solver::astar(n, &Heuristic::parse("manhattan").unwrap()).unwrap();
+
+        assert!(moves_string(&end) == "L");
+        assert!(csv(&end) == "step,move,t0,t1,t2,t3,t4,t5,t6,t7,t8\n0,,1,2,3,8,4,0,7,6,5\n1,L,1,2,3,8,0,4,7,6,5");
+
+        let stats = Stats { total_states: stats.total_states, max_states: 3 };
+        assert!(json(&end, &stats).starts_with("{\"size\":3,\"length\":1,\"moves\":\"L\",\"boards\":[[1,2,3,8,4,0,7,6,5],[1,2,3,8,0,4,7,6,5]]"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Line-delimited JSON log of every node popped from the open heap.
+
+use node::Node;
+use solver::{Observer, Outcome};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+pub struct Trace {
+    out: BufWriter<File>,
+    step: usize,
+    /// Entry of the node being expanded, waiting for its neighbours.
+    pending: Option<String>,
+    neighbours: Vec<String>,
+    error: Option<io::Error>,
+}
+
+fn board(n: &Node) -> String {
+    format!("[{}]", n.board.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(","))
+}
+
+impl Trace {
+    pub fn create(path: &str) -> io::Result<Trace> {
+        Ok(Trace {
+            out: BufWriter::new(File::create(path)?),
+            step: 0,
+            pending: None,
+            neighbours: Vec::new(),
+            error: None,
+        })
+    }
+
+    fn flush_pending(&mut self) {
+        if let Some(entry) = self.pending.take() {
+            let line = format!("{},\"neighbours\":[{}]}}", entry, self.neighbours.join(","));
+            self.neighbours.clear();
+            if self.error.is_none() {
+                if let Err(e) = writeln!(self.out, "{}", line) {
+                    self.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Writes the last entry and reports the first error met while tracing.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.flush_pending();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.out.flush(),
+        }
+    }
+}
+
+impl Observer for Trace {
+    fn expand(&mut self, node: &Node, open: usize, closed: usize) {
+        self.flush_pending();
+        self.pending = Some(format!("{{\"step\":{},\"board\":{},\"g\":{},\"h\":{},\"f\":{},\"open\":{},\"closed\":{}",
+                                    self.step, board(node), node.cost, node.heuristic,
+                                    node.cost + node.heuristic, open, closed));
+        self.step += 1;
+    }
+
+    fn generate(&mut self, _parent: &Node, child: &Node, outcome: Outcome) {
+        let outcome = match outcome {
+            Outcome::Pushed => "pushed",
+            Outcome::Reopened => "reopened",
+            Outcome::Closed => "closed",
+            Outcome::Opened => "opened",
+        };
+        self.neighbours.push(format!("{{\"board\":{},\"g\":{},\"h\":{},\"outcome\":\"{}\"}}",
+                                     board(child), child.cost, child.heuristic, outcome));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Trace;
+    use heuristics::Heuristic;
+    use node::Node;
+    use solver;
+    use solver::open::{self, Queue, TieBreak};
+    use std::fs::File;
+    use std::io::Read;
+
+    #[test]
+    fn trace_test() {
+        let n: Node = "3\n1 2 3\n0 8 4\n7 6 5\n".parse().expect("Map should be valid");
+        let path = ::std::env::temp_dir().join("npuzzle_trace_test.jsonl");
+        let path = path.to_str().unwrap();
+
+        let mut trace = Trace::create(path).unwrap();
+        solver::astar_observed(n, &Heuristic::parse("manhattan").unwrap(), open::build(Queue::Heap, TieBreak::Arbitrary), &mut trace).unwrap();
+        trace.finish().unwrap();
+
+        let mut s = String::new();
+        File::open(path).unwrap().read_to_string(&mut s).unwrap();
+        let lines = s.lines().collect::<Vec<_>>();
+        assert!(lines.len() == 2);
+        assert!(lines[0].starts_with("{\"step\":0,\"board\":[1,2,3,0,8,4,7,6,5],\"g\":0"));
+        assert!(lines[0].contains("\"outcome\":\"pushed\""));
+        assert!(lines[1].ends_with("\"neighbours\":[]}"));
+    }
+}
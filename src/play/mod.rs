@@ -0,0 +1,245 @@
+//! Interactive mode where a human slides the tiles.
+
+use heuristics::Heuristic;
+use node::{Direction, Node};
+use solver;
+use std::io::{self, Write};
+use term::{self, Key, Terminal};
+use verify::{self, Move};
+
+pub struct Game {
+    pub board: Vec<usize>,
+    pub len: usize,
+    history: Vec<Direction>,
+    undone: Vec<Direction>,
+}
+
+impl Game {
+    pub fn new(n: &Node) -> Game {
+        Game {
+            board: n.board.clone(),
+            len: n.len,
+            history: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn node(&self) -> Node {
+        Node {
+            board: self.board.clone(),
+            len: self.len,
+            cost: 0,
+            heuristic: 0,
+            parents: None,
+        }
+    }
+
+    pub fn moves(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.node() == Node::goal(self.len)
+    }
+
+    /// Moves the blank, returning false when the move is not possible.
+    pub fn play(&mut self, d: Direction) -> bool {
+        if verify::apply(&mut self.board, self.len, Move::Blank(d)).is_err() {
+            return false;
+        }
+        self.history.push(d);
+        self.undone.clear();
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(d) => {
+                let _ = verify::apply(&mut self.board, self.len, Move::Blank(d.opposite()));
+                self.undone.push(d);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(d) => {
+                let _ = verify::apply(&mut self.board, self.len, Move::Blank(d));
+                self.history.push(d);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Expansions allowed to a hint on boards larger than 3x3, past which the
+/// game would stop responding.
+const HINT_BUDGET: usize = 1_000_000;
+
+/// First move of a shortest solution from the current board. Boards up to
+/// 3x3 get an exact answer, larger ones an IDA* on the `tiles` heuristic
+/// that gives up after `HINT_BUDGET` expansions.
+fn hint(game: &Game) -> Option<Direction> {
+    if game.len > 3 {
+        let blank = game.board.iter().position(|&v| v == 0)?;
+        let goal = Node::goal(game.len);
+        let path = ::optimize::shortest(&game.board, blank, &goal.board, game.len, usize::MAX - 1, HINT_BUDGET)?;
+        return path.first().map(|&next| ::optimize::direction(blank, next, game.len));
+    }
+    let (end, _) = solver::bidirectional::search(game.node())?;
+
+    let path = end.parents().collect::<Vec<_>>();
+    if path.len() < 2 {
+        return None;
+    }
+    match ::output::blank_move(path[path.len() - 1], path[path.len() - 2]) {
+        Move::Blank(d) => Some(d),
+        Move::Tile(_) => None,
+    }
+}
+
+fn draw(game: &Game, previous: &[usize], h: &Heuristic, message: &str) {
+    let len = game.len;
+    let colours = Node::format_colors(&game.board, previous);
+
+    term::clear();
+    for x in 0..len {
+        let colored_numbers = colours[x * len..x * len + len].iter().map(|&(c, v)| {
+            c.paint(format!("{0:<3}", v.to_string())).to_string()
+        }).collect::<Vec<_>>().join(" ");
+        print!("{}\r\n", colored_numbers);
+    }
+    print!("\r\nmoves: {}  heuristic ({}): {}\r\n", game.moves(), h, h.eval(&game.node()));
+    print!("{}\r\n", message);
+    print!("arrows/wasd: slide  u: undo  r: redo  h: hint  q: quit\r\n");
+    let _ = io::stdout().flush();
+}
+
+/// Arrow keys name the direction the tile travels, so the blank goes the
+/// other way.
+fn key_direction(key: Key) -> Option<Direction> {
+    match key {
+        Key::Up | Key::Char('w') => Some(Direction::South),
+        Key::Down | Key::Char('s') => Some(Direction::North),
+        Key::Left | Key::Char('a') => Some(Direction::East),
+        Key::Right | Key::Char('d') => Some(Direction::West),
+        _ => None,
+    }
+}
+
+pub fn run(n: Node, h: &Heuristic) {
+    let terminal = Terminal::raw();
+    if !terminal.is_interactive() {
+        println!("Error: play needs an interactive terminal");
+        return;
+    }
+
+    let mut game = Game::new(&n);
+    let mut previous = game.board.clone();
+    let mut message = String::new();
+
+    loop {
+        if game.is_solved() {
+            message = format!("Solved in {} moves! u: undo  q: quit", game.moves());
+        }
+        draw(&game, &previous, h, &message);
+
+        let key = match terminal.read_key(None) {
+            Some(k) => k,
+            None => continue,
+        };
+        let before = game.board.clone();
+        message.clear();
+
+        let changed = match key {
+            Key::Char('q') | Key::Escape => break,
+            Key::Char('u') => game.undo(),
+            Key::Char('r') => game.redo(),
+            Key::Char('h') => {
+                message = match hint(&game) {
+                    Some(d) => {
+                        let tile = game.board[target(&game, d)];
+                        format!("hint: slide tile {} ({})", tile, Move::Blank(d))
+                    }
+                    None if game.is_solved() => "hint: nothing to do".to_string(),
+                    None => "no hint".to_string(),
+                };
+                false
+            }
+            k => match key_direction(k) {
+                Some(d) if !game.is_solved() => game.play(d),
+                _ => false,
+            },
+        };
+
+        if changed {
+            previous = before;
+        }
+    }
+}
+
+fn target(game: &Game, d: Direction) -> usize {
+    let mut board = game.board.clone();
+    let blank = board.iter().position(|&v| v == 0).unwrap_or(0);
+    let _ = verify::apply(&mut board, game.len, Move::Blank(d));
+    board.iter().position(|&v| v == 0).unwrap_or(blank)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hint, key_direction, Game};
+    use node::{Direction, Node};
+    use term::Key;
+
+    #[test]
+    fn game_undo_redo_test() {
+        let n: Node = "3\n1 2 3\n8 4 0\n7 6 5\n".parse().expect("Map should be valid");
+        let mut game = Game::new(&n);
+
+        assert!(!game.play(Direction::East));
+        assert!(game.play(Direction::South));
+        assert!(game.undo());
+        assert!(game.board == n.board);
+        assert!(game.redo());
+        assert!(!game.redo());
+        assert!(game.undo());
+        assert!(game.play(Direction::West));
+        assert!(game.is_solved());
+        assert!(game.moves() == 1);
+        assert!(!game.redo());
+    }
+
+    #[test]
+    fn arrow_keys_test() {
+        let n: Node = "3\n1 2 3\n8 4 0\n7 6 5\n".parse().expect("Map should be valid");
+        let mut game = Game::new(&n);
+        let mut press = |k| key_direction(k).map(|d| game.play(d));
+
+        // Left slides the tile right of the blank, and the blank is on the
+        // right edge
+        assert!(press(Key::Left) == Some(false));
+        assert!(press(Key::Char('q')).is_none());
+        assert!(press(Key::Up) == Some(true));
+        assert!(press(Key::Down) == Some(true));
+        assert!(press(Key::Right) == Some(true));
+        assert!(game.is_solved());
+        assert!(game.moves() == 3);
+    }
+
+    #[test]
+    fn hint_test() {
+        let n: Node = "4\n1 2 3 4\n12 13 14 5\n11 15 0 6\n10 9 8 7\n".parse().expect("Map should be valid");
+        let mut game = Game::new(&n);
+        assert!(hint(&game) == Some(Direction::West));
+        assert!(game.play(Direction::West));
+        assert!(game.is_solved());
+        assert!(hint(&game).is_none());
+
+        // Far too scrambled for the budget: no hint rather than a frozen game
+        let game = Game::new(&Node::random(6, 10000, true));
+        assert!(hint(&game).is_none());
+    }
+}
@@ -0,0 +1,194 @@
+//! Small GIF89a encoder for paletted animations, written by hand so the
+//! exports do not need any image library.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+pub struct Frame {
+    /// One palette index per pixel, row by row.
+    pub pixels: Vec<u8>,
+    /// Time the frame stays on screen, in hundredths of a second.
+    pub delay: u16,
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.current |= u32::from(code) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push((self.current & 0xff) as u8);
+            self.current >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push((self.current & 0xff) as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Variable width LZW as specified for GIF image data.
+pub fn lzw(min_code_size: u8, pixels: &[u8]) -> Vec<u8> {
+    let clear = 1_u16 << min_code_size;
+    let end = clear + 1;
+    let mut size = u32::from(min_code_size) + 1;
+    let mut next = end + 1;
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut out = BitWriter { bytes: Vec::new(), current: 0, bits: 0 };
+
+    out.write(clear, size);
+    let mut it = pixels.iter();
+    let mut prefix = match it.next() {
+        Some(&p) => u16::from(p),
+        None => {
+            out.write(end, size);
+            return out.finish();
+        }
+    };
+
+    for &k in it {
+        if let Some(&code) = dict.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+
+        out.write(prefix, size);
+        dict.insert((prefix, k), next);
+        next += 1;
+        if next > (1 << size) && size < 12 {
+            size += 1;
+        }
+        if next == 4096 {
+            out.write(clear, size);
+            dict.clear();
+            size = u32::from(min_code_size) + 1;
+            next = end + 1;
+        }
+        prefix = u16::from(k);
+    }
+
+    out.write(prefix, size);
+    out.write(end, size);
+    out.finish()
+}
+
+pub fn encode<W: Write>(w: &mut W, width: u16, height: u16, palette: &[[u8; 3]], frames: &[Frame]) -> io::Result<()> {
+    // The colour table size is a power of two, at least 4 for the LZW code size
+    let mut bits = 2;
+    while (1 << bits) < palette.len() {
+        bits += 1;
+    }
+
+    w.write_all(b"GIF89a")?;
+    w.write_all(&width.to_le_bytes())?;
+    w.write_all(&height.to_le_bytes())?;
+    w.write_all(&[0x80 | (bits - 1), 0, 0])?;
+    for i in 0..(1 << bits) {
+        w.write_all(palette.get(i).unwrap_or(&[0, 0, 0]))?;
+    }
+
+    // Loop forever
+    w.write_all(&[0x21, 0xff, 0x0b])?;
+    w.write_all(b"NETSCAPE2.0")?;
+    w.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+    for frame in frames {
+        w.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        w.write_all(&frame.delay.to_le_bytes())?;
+        w.write_all(&[0x00, 0x00])?;
+
+        w.write_all(&[0x2c, 0, 0, 0, 0])?;
+        w.write_all(&width.to_le_bytes())?;
+        w.write_all(&height.to_le_bytes())?;
+        w.write_all(&[0x00, bits])?;
+        for block in lzw(bits, &frame.pixels).chunks(255) {
+            w.write_all(&[block.len() as u8])?;
+            w.write_all(block)?;
+        }
+        w.write_all(&[0x00])?;
+    }
+
+    w.write_all(&[0x3b])
+}
+
+#[cfg(test)]
+mod test {
+    use super::lzw;
+
+    /// Reference decoder, mirroring what image viewers do.
+    fn decode(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+        let clear = 1_usize << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> { (0..end + 1).map(|i| vec![i as u8]).collect() };
+        let mut dict = reset();
+        let mut size = min_code_size as usize + 1;
+        let mut old: Option<usize> = None;
+        let mut out = Vec::new();
+        let (mut acc, mut bits, mut pos) = (0_usize, 0, 0);
+
+        loop {
+            while bits < size {
+                acc |= (data[pos] as usize) << bits;
+                pos += 1;
+                bits += 8;
+            }
+            let code = acc & ((1 << size) - 1);
+            acc >>= size;
+            bits -= size;
+
+            if code == clear {
+                dict = reset();
+                size = min_code_size as usize + 1;
+                old = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match old {
+                None => dict[code].clone(),
+                Some(o) => {
+                    let entry = if code < dict.len() {
+                        dict[code].clone()
+                    } else {
+                        let mut e = dict[o].clone();
+                        e.push(dict[o][0]);
+                        e
+                    };
+                    if dict.len() < 4096 {
+                        let mut added = dict[o].clone();
+                        added.push(entry[0]);
+                        dict.push(added);
+                        if dict.len() >= (1 << size) && size < 12 {
+                            size += 1;
+                        }
+                    }
+                    entry
+                }
+            };
+            out.extend_from_slice(&entry);
+            old = Some(code);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip_test() {
+        let mut pixels = Vec::new();
+        for i in 0..20_000_usize {
+            pixels.push(((i * 7 + i / 13) % 5) as u8);
+        }
+        assert!(decode(3, &lzw(3, &pixels)) == pixels);
+
+        let flat = vec![1_u8; 5000];
+        assert!(decode(2, &lzw(2, &flat)) == flat);
+    }
+}
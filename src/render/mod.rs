@@ -0,0 +1,164 @@
+//! SVG rendering of boards, alone or as a strip of the whole solution, for
+//! documents where the ANSI output of `print_result` cannot be embedded.
+
+pub mod gif;
+pub mod raster;
+
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Svg,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "svg" => Ok(Format::Svg),
+            f => Err(format!("Invalid value for format: {}, possible values are: \
+                 \n\tsvg: Scalable Vector Graphics", f)),
+        }
+    }
+}
+
+pub struct Style {
+    pub tile_size: usize,
+    pub gap: usize,
+    pub background: String,
+    pub tile: String,
+    pub text: String,
+    /// Fill of the tile that just moved, `None` to disable highlighting.
+    pub highlight: Option<String>,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            tile_size: 40,
+            gap: 20,
+            background: "#ffffff".to_string(),
+            tile: "#dddddd".to_string(),
+            text: "#222222".to_string(),
+            highlight: None,
+        }
+    }
+}
+
+/// Tile that moved between two boards, i.e. the one now where the blank was.
+fn moved_tile(previous: &[usize], board: &[usize]) -> Option<usize> {
+    previous.iter().position(|&v| v == 0).map(|p| board[p]).filter(|&t| t != 0)
+}
+
+fn board_svg(out: &mut String, board: &[usize], moved: Option<usize>, len: usize, x: usize, y: usize, style: &Style) {
+    let t = style.tile_size;
+
+    for (i, &v) in board.iter().enumerate() {
+        if v == 0 {
+            continue;
+        }
+        let (tx, ty) = (x + (i % len) * t, y + (i / len) * t);
+        let fill = match style.highlight {
+            Some(ref h) if moved == Some(v) => h,
+            _ => &style.tile,
+        };
+        let _ = writeln!(out,
+                       "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"{}\" stroke=\"{}\"/>\
+                        <text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" \
+                        font-family=\"sans-serif\" fill=\"{}\">{}</text>",
+                       tx + 1, ty + 1, t - 2, t - 2, t / 8, fill, style.text,
+                       tx + t / 2, ty + t / 2, t * 2 / 5, style.text, v);
+    }
+}
+
+/// Lays out `boards` left to right, wrapping after `columns` boards. A single
+/// board gives a plain diagram, a whole solution gives a filmstrip.
+pub fn svg(boards: &[Vec<usize>], len: usize, style: &Style, columns: usize) -> String {
+    let columns = columns.max(1).min(boards.len().max(1));
+    let rows = boards.len().div_ceil(columns).max(1);
+    let side = len * style.tile_size;
+    let width = columns * side + (columns + 1) * style.gap;
+    let height = rows * side + (rows + 1) * style.gap;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+                     width, height);
+    let _ = writeln!(out, "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>", width, height, style.background);
+
+    for (i, board) in boards.iter().enumerate() {
+        let x = style.gap + (i % columns) * (side + style.gap);
+        let y = style.gap + (i / columns) * (side + style.gap);
+        let moved = if i == 0 { None } else { moved_tile(&boards[i - 1], board) };
+        let _ = writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+                         x, y, side, side, style.text);
+        board_svg(&mut out, board, moved, len, x, y, style);
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Writes the solution as an animated GIF, one frame per board. With
+/// `smooth`, a few extra frames slide the moving tile between cells.
+pub fn export_gif(path: &str, boards: &[Vec<usize>], len: usize, style: &Style, delay_ms: u64, smooth: bool) -> io::Result<()> {
+    const SLIDE_FRAMES: usize = 4;
+
+    let r = raster::Raster { len, style };
+    let (width, height) = r.size();
+    if width > usize::from(u16::MAX) || height > usize::from(u16::MAX) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "GIF images are at most 65535 pixels wide, use a smaller --tile-size"));
+    }
+    let hold = (delay_ms / 10).clamp(1, u64::from(u16::MAX)) as u16;
+    let slide = (hold / (2 * SLIDE_FRAMES as u16)).max(2);
+    let mut frames = Vec::new();
+
+    for (i, board) in boards.iter().enumerate() {
+        let moved = if i == 0 { None } else { moved_tile(&boards[i - 1], board) };
+
+        if let (true, Some(tile)) = (smooth, moved) {
+            let from = boards[i - 1].iter().position(|&v| v == tile).unwrap_or(0);
+            for step in 1..SLIDE_FRAMES {
+                let progress = step as f64 / SLIDE_FRAMES as f64;
+                frames.push(gif::Frame {
+                    pixels: r.draw(board, moved, Some((from, progress))).pixels,
+                    delay: slide,
+                });
+            }
+        }
+
+        let last = i + 1 == boards.len();
+        frames.push(gif::Frame {
+            pixels: r.draw(board, moved, None).pixels,
+            delay: if last { hold.max(100) } else { hold },
+        });
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    gif::encode(&mut out, width as u16, height as u16, &r.palette(), &frames)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{svg, Style};
+
+    #[test]
+    fn svg_render_test() {
+        let boards = vec![vec![1, 2, 3, 8, 4, 0, 7, 6, 5], vec![1, 2, 3, 8, 0, 4, 7, 6, 5]];
+        let style = Style {
+            highlight: Some("#00aa00".to_string()),
+            ..Style::default()
+        };
+
+        let out = svg(&boards, 3, &style, 10);
+        assert!(out.starts_with("<svg"));
+        assert!(out.contains("width=\"300\" height=\"160\""));
+        assert!(out.matches("<text").count() == 16);
+        assert!(out.matches("fill=\"#00aa00\"").count() == 1);
+
+        let out = svg(&boards[..1], 3, &Style::default(), 1);
+        assert!(out.matches("<text").count() == 8);
+    }
+}
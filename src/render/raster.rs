@@ -0,0 +1,147 @@
+//! Rasterizes boards into paletted frames for the GIF export.
+
+use render::Style;
+
+const BACKGROUND: u8 = 0;
+const TILE: u8 = 1;
+const TEXT: u8 = 2;
+const HIGHLIGHT: u8 = 3;
+
+/// 3x5 bitmap digits, one row per byte, most significant of 3 bits on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+fn parse_colour(s: &str) -> Option<[u8; 3]> {
+    let hex = s.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn rect(&mut self, x: usize, y: usize, w: usize, h: usize, colour: u8) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                self.pixels[py * self.width + px] = colour;
+            }
+        }
+    }
+
+    fn number(&mut self, v: usize, cx: usize, cy: usize, scale: usize) {
+        let digits = v.to_string().bytes().map(|b| (b - b'0') as usize).collect::<Vec<_>>();
+        let width = digits.len() * 4 * scale - scale;
+        let x0 = cx.saturating_sub(width / 2);
+        let y0 = cy.saturating_sub(5 * scale / 2);
+
+        for (i, &d) in digits.iter().enumerate() {
+            for (row, bits) in DIGITS[d].iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.rect(x0 + (i * 4 + col) * scale, y0 + row * scale, scale, scale, TEXT);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct Raster<'a> {
+    pub len: usize,
+    pub style: &'a Style,
+}
+
+impl<'a> Raster<'a> {
+    pub fn palette(&self) -> Vec<[u8; 3]> {
+        let defaults = Style::default();
+        let colour = |s: &str, d: &str| parse_colour(s).or_else(|| parse_colour(d)).unwrap_or([0, 0, 0]);
+        vec![
+            colour(&self.style.background, &defaults.background),
+            colour(&self.style.tile, &defaults.tile),
+            colour(&self.style.text, &defaults.text),
+            colour(self.style.highlight.as_ref().unwrap_or(&self.style.tile), &defaults.tile),
+        ]
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        let side = self.len * self.style.tile_size + 2 * self.style.gap;
+        (side, side)
+    }
+
+    fn tile(&self, canvas: &mut Canvas, v: usize, x: usize, y: usize, highlight: bool) {
+        let t = self.style.tile_size;
+        canvas.rect(x + 1, y + 1, t - 2, t - 2, TEXT);
+        canvas.rect(x + 2, y + 2, t - 4, t - 4, if highlight { HIGHLIGHT } else { TILE });
+        canvas.number(v, x + t / 2, y + t / 2, (t / 16).max(1));
+    }
+
+    /// Draws `board`. When `slide` is given as (from, progress in 0..=1), the
+    /// `moved` tile is drawn part way between its previous cell `from` and
+    /// its new one.
+    pub fn draw(&self, board: &[usize], moved: Option<usize>, slide: Option<(usize, f64)>) -> Canvas {
+        let (width, height) = self.size();
+        let t = self.style.tile_size;
+        let mut canvas = Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        };
+        let origin = |i: usize| (self.style.gap + (i % self.len) * t, self.style.gap + (i / self.len) * t);
+
+        for (i, &v) in board.iter().enumerate() {
+            if v == 0 {
+                continue;
+            }
+            let (mut x, mut y) = origin(i);
+            if let (Some(m), Some((from, progress))) = (moved, slide) {
+                if m == v {
+                    let (fx, fy) = origin(from);
+                    x = (fx as f64 + (x as f64 - fx as f64) * progress).round() as usize;
+                    y = (fy as f64 + (y as f64 - fy as f64) * progress).round() as usize;
+                }
+            }
+            self.tile(&mut canvas, v, x, y, moved == Some(v) && self.style.highlight.is_some());
+        }
+
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_colour, Raster, BACKGROUND, TEXT};
+    use render::Style;
+
+    #[test]
+    fn raster_test() {
+        assert!(parse_colour("#ff8000") == Some([255, 128, 0]));
+        assert!(parse_colour("red").is_none());
+        assert!(parse_colour("aébcd").is_none());
+
+        let style = Style::default();
+        let raster = Raster { len: 2, style: &style };
+        let canvas = raster.draw(&[1, 2, 3, 0], None, None);
+        assert!(canvas.width == 120 && canvas.height == 120);
+        assert!(canvas.pixels[0] == BACKGROUND);
+        assert!(canvas.pixels.contains(&TEXT));
+        // The blank cell stays empty
+        assert!(canvas.pixels[80 * 120 + 80] == BACKGROUND);
+    }
+}
@@ -0,0 +1,61 @@
+//! Animated replay of a solution, redrawing a single board in place.
+
+use node::Node;
+use std::io::{self, Write};
+use std::time::Duration;
+use term::{self, Key, Terminal};
+
+const MIN_DELAY: u64 = 10;
+const MAX_DELAY: u64 = 5000;
+
+fn draw(boards: &[Vec<usize>], len: usize, step: usize, delay: u64, paused: bool) {
+    let previous = if step == 0 { &boards[0] } else { &boards[step - 1] };
+    let colours = Node::format_colors(&boards[step], previous);
+
+    term::clear();
+    for x in 0..len {
+        let colored_numbers = colours[x * len..x * len + len].iter().map(|&(c, v)| {
+            c.paint(format!("{0:<3}", v.to_string())).to_string()
+        }).collect::<Vec<_>>().join(" ");
+        print!("{}\r\n", colored_numbers);
+    }
+    print!("\r\nstep {}/{}  delay {}ms{}\r\n",
+           step, boards.len() - 1, delay, if paused { "  [paused]" } else { "" });
+    print!("space: pause  left/right: step  +/-: speed  q: quit\r\n");
+    let _ = io::stdout().flush();
+}
+
+/// Plays `boards` one after the other, `delay` milliseconds apart.
+pub fn animate(boards: &[Vec<usize>], len: usize, delay: u64) {
+    if boards.is_empty() {
+        return;
+    }
+
+    let terminal = Terminal::raw();
+    let mut delay = delay.clamp(MIN_DELAY, MAX_DELAY);
+    let mut step = 0;
+    let mut paused = false;
+
+    loop {
+        draw(boards, len, step, delay, paused);
+        let timeout = if paused { None } else { Some(Duration::from_millis(delay)) };
+
+        match terminal.read_key(timeout) {
+            None if step + 1 < boards.len() => step += 1,
+            None => break,
+            Some(Key::Char(' ')) => paused = !paused,
+            Some(Key::Left) | Some(Key::Char('b')) => {
+                paused = true;
+                step = step.saturating_sub(1);
+            }
+            Some(Key::Right) | Some(Key::Char('n')) => {
+                paused = true;
+                step = (step + 1).min(boards.len() - 1);
+            }
+            Some(Key::Char('+')) | Some(Key::Up) => delay = (delay / 2).max(MIN_DELAY),
+            Some(Key::Char('-')) | Some(Key::Down) => delay = (delay * 2).min(MAX_DELAY),
+            Some(Key::Char('q')) | Some(Key::Escape) => break,
+            Some(_) => {}
+        }
+    }
+}
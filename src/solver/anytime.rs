@@ -0,0 +1,136 @@
+//! Anytime weighted A*: the heap is ordered on g + w * h so a first solution
+//! comes quickly, then the search goes on, pruning every node whose plain
+//! g + h cannot beat the incumbent. Each improvement is reported with a lower
+//! bound on the optimal length, the smallest g + h left in the open list.
+//! When the open list runs dry the incumbent is optimal, provided the
+//! heuristic is admissible.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::Stats;
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+struct Entry {
+    /// g + w * h, scaled by 1000 to stay in integers.
+    key: usize,
+    node: Arc<Node>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Entry {}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> cmp::Ordering {
+        // Deeper first among equal keys, to reach a solution sooner
+        Reverse(self.key).cmp(&Reverse(other.key)).then(self.node.cost.cmp(&other.node.cost))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Searches until the incumbent is proven optimal or `stop` is raised, and
+/// calls `report` with every improved solution and the lower bound known at
+/// that time.
+pub fn search(n: Node,
+              h: &Heuristic,
+              weight: f64,
+              stop: &AtomicBool,
+              report: &mut dyn FnMut(&Node, usize)) -> Option<(Arc<Node>, Stats)> {
+    let goal = Node::goal(n.len);
+    let scale = (weight.max(1.0) * 1000.0).round() as usize;
+    let key = |n: &Node| n.cost * 1000 + n.heuristic * scale;
+
+    let mut n = n;
+    n.heuristic = h.eval(&n);
+    let root = Arc::new(n);
+    let mut best_g: HashMap<Vec<usize>, usize> = HashMap::new();
+    let mut open = BinaryHeap::new();
+    best_g.insert(root.board.clone(), 0);
+    open.push(Entry { key: key(&root), node: root });
+
+    let mut incumbent: Option<Arc<Node>> = None;
+    let mut stats = Stats {
+        total_states: 1,
+        max_states: 1,
+    };
+
+    while let Some(Entry { node, .. }) = open.pop() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let bound = incumbent.as_ref().map(|i| i.cost).unwrap_or(usize::MAX);
+        if best_g[&node.board] != node.cost || node.cost + node.heuristic >= bound {
+            continue;
+        }
+
+        if *node == goal {
+            let lower = open.iter()
+                .map(|e| e.node.cost + e.node.heuristic)
+                .filter(|&f| f < node.cost)
+                .min()
+                .unwrap_or(node.cost);
+            report(&node, lower);
+            incumbent = Some(node);
+            continue;
+        }
+
+        for child in Node::get_next_steps(&node, h) {
+            if child.cost + child.heuristic >= bound {
+                continue;
+            }
+            if best_g.get(&child.board).map(|&g| g <= child.cost).unwrap_or(false) {
+                continue;
+            }
+            best_g.insert(child.board.clone(), child.cost);
+            stats.total_states += 1;
+            open.push(Entry { key: key(&child), node: Arc::new(child) });
+        }
+        stats.max_states = cmp::max(stats.max_states, open.len());
+    }
+
+    incumbent.map(|node| (node, stats))
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics;
+    use heuristics::Heuristic;
+    use node::Node;
+    use solver;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn anytime_improves_up_to_optimal() {
+        let h = Heuristic::Base("exact", heuristics::eval_exact);
+        let zero = Heuristic::Base("zero", |_| 0);
+        let n: Node = "3\n0 4 3\n8 1 7\n2 5 6\n".parse().expect("Map should be valid");
+        let (optimal, _) = solver::astar(n.clone(), &zero).unwrap();
+
+        // Halving the exact heuristic keeps it admissible but loose enough
+        // for the high weight to find a poor first solution.
+        let half = Heuristic::Base("half", |n| heuristics::eval_exact(n) / 2);
+        let mut reports = Vec::new();
+        let (found, _) = super::search(n.clone(), &half, 5.0, &AtomicBool::new(false), &mut |s, lower| {
+            reports.push((s.cost, lower))
+        }).unwrap();
+        assert!(found.cost == optimal.cost);
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|w| w[1].0 < w[0].0));
+        assert!(reports.iter().all(|&(cost, lower)| lower <= optimal.cost && optimal.cost <= cost));
+
+        let stop = AtomicBool::new(true);
+        assert!(super::search(n, &h, 5.0, &stop, &mut |_, _| ()).is_none());
+    }
+}
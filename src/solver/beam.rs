@@ -0,0 +1,87 @@
+//! Beam search: breadth-first, but only the `width` children with the lowest
+//! heuristic are kept at every depth. Boards kept at an earlier depth are
+//! never kept again, which stops the beam from cycling. Neither complete nor
+//! optimal, but each depth costs time linear in the width, so it still finds
+//! a solution on boards far beyond the reach of exact methods.
+//!
+//! Only hashes of the kept boards are remembered: a collision merely drops a
+//! child, while full boards would not fit in memory on long searches. The
+//! hashes are never dropped, so memory grows linearly in depth × width. The
+//! `tiles` heuristic guides the beam far better than `manhattan`, whose pull
+//! on the blank leads it astray on large boards.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::Stats;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+fn fingerprint(board: &[usize]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn search(n: Node, h: &Heuristic, width: usize) -> Option<(Arc<Node>, Stats)> {
+    let goal = Node::goal(n.len);
+    let width = width.max(1);
+    let mut seen: HashSet<u64> = HashSet::new();
+    seen.insert(fingerprint(&n.board));
+    let mut beam = vec![Arc::new(n)];
+    let mut stats = Stats {
+        total_states: 1,
+        max_states: 1,
+    };
+
+    loop {
+        if let Some(found) = beam.iter().find(|b| ***b == goal) {
+            stats.max_states = seen.len();
+            return Some((found.clone(), stats));
+        }
+
+        let mut children = Vec::new();
+        let mut layer: HashSet<u64> = HashSet::new();
+        for node in &beam {
+            for child in Node::get_next_steps(node, h) {
+                let print = fingerprint(&child.board);
+                if !seen.contains(&print) && layer.insert(print) {
+                    children.push(child);
+                }
+            }
+        }
+        if children.is_empty() {
+            return None;
+        }
+        stats.total_states += children.len();
+
+        if children.len() > width {
+            children.select_nth_unstable_by_key(width - 1, |c| c.heuristic);
+            children.truncate(width);
+        }
+        for c in &children {
+            seen.insert(fingerprint(&c.board));
+        }
+        beam = children.into_iter().map(Arc::new).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::{self, Heuristic};
+    use node::Node;
+
+    #[test]
+    fn beam_solves_large_boards() {
+        let h = Heuristic::parse("tiles").unwrap();
+        let n = Node::random(7, 100, true);
+        let (found, _) = super::search(n.clone(), &h, 1000).expect("beam should find a solution");
+        assert!(*found == Node::goal(7));
+
+        let path = found.parents().collect::<Vec<_>>();
+        assert!(*path[path.len() - 1] == n);
+        assert!(path.len() == found.cost + 1);
+        assert!(found.cost >= heuristics::eval_tiles(&n));
+    }
+}
@@ -0,0 +1,125 @@
+//! Bidirectional breadth-first search. Since `Node::goal` is known up front we
+//! can grow one frontier from the start board and one from the goal, always
+//! expanding the smaller one, and stitch both halves together where they meet.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::Stats;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+type Visited = HashMap<Vec<usize>, Arc<Node>>;
+
+fn no_heuristic(_: &Node) -> usize {
+    0
+}
+
+/// Expands a whole layer of `frontier` and returns the best meeting point
+/// found against `other`, as (node on this side, node on the other side).
+fn expand(h: &Heuristic,
+          frontier: &mut Vec<Arc<Node>>,
+          visited: &mut Visited,
+          other: &Visited) -> Option<(Arc<Node>, Arc<Node>)> {
+    let mut next = Vec::new();
+    let mut meeting: Option<(Arc<Node>, Arc<Node>)> = None;
+
+    for node in frontier.iter() {
+        for child in Node::get_next_steps(node, h) {
+            if visited.contains_key(&child.board) {
+                continue;
+            }
+            let child = Arc::new(child);
+            if let Some(o) = other.get(&child.board) {
+                let better = meeting.as_ref()
+                    .map(|(a, b)| child.cost + o.cost < a.cost + b.cost)
+                    .unwrap_or(true);
+                if better {
+                    meeting = Some((child.clone(), o.clone()));
+                }
+            }
+            visited.insert(child.board.clone(), child.clone());
+            next.push(child);
+        }
+    }
+
+    *frontier = next;
+    meeting
+}
+
+/// Turns the two half-paths into a single chain ending on the goal, so the
+/// result can be walked with `Node::parents` like any other solution.
+fn stitch(forward: Arc<Node>, backward: &Node) -> Arc<Node> {
+    let mut current = forward;
+    for n in backward.parents().skip(1) {
+        current = Arc::new(Node {
+            board: n.board.clone(),
+            len: n.len,
+            cost: current.cost + 1,
+            heuristic: 0,
+            parents: Some(current),
+        });
+    }
+
+    current
+}
+
+pub fn search(n: Node) -> Option<(Arc<Node>, Stats)> {
+    let goal = Node::goal(n.len);
+    let start = Arc::new(n);
+    let end = Arc::new(goal);
+
+    let mut stats = Stats {
+        total_states: 2,
+        max_states: 2,
+    };
+
+    if *start == *end {
+        return Some((start, stats));
+    }
+
+    let mut forward: Visited = HashMap::new();
+    let mut backward: Visited = HashMap::new();
+    forward.insert(start.board.clone(), start.clone());
+    backward.insert(end.board.clone(), end.clone());
+    let h = Heuristic::Base("none", no_heuristic);
+    let mut forward_frontier = vec![start];
+    let mut backward_frontier = vec![end];
+
+    while !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+        let meeting = if forward_frontier.len() <= backward_frontier.len() {
+            expand(&h, &mut forward_frontier, &mut forward, &backward)
+        } else {
+            expand(&h, &mut backward_frontier, &mut backward, &forward)
+                .map(|(b, f)| (f, b))
+        };
+
+        stats.total_states = forward.len() + backward.len();
+        stats.max_states = stats.max_states.max(stats.total_states);
+
+        if let Some((f, b)) = meeting {
+            return Some((stitch(f, &b), stats));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use solver::fixture;
+
+    #[test]
+    fn bidirectional_finds_optimal_path() {
+        for (n, d) in fixture::boards() {
+            let (end, _) = super::search(n.clone()).expect("reachable board should be solved");
+            fixture::assert_path(&n, &end, d);
+        }
+    }
+
+    #[test]
+    fn bidirectional_gives_up_on_unsolvable() {
+        let (mut n, _) = fixture::boards().remove(2);
+        n.make_unsolvable();
+        assert!(super::search(n).is_none());
+    }
+}
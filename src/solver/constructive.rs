@@ -0,0 +1,304 @@
+//! Constructive solver, the way people solve the puzzle by hand. The board
+//! is shrunk one side at a time in the snail order of `Node::goal`: top row,
+//! right column, bottom row, left column, then again on the inner rectangle.
+//! Tiles of a side are brought in one by one and locked, except for the last
+//! two which go in together: the corner tile is parked in the cell before the
+//! corner, its neighbour right inside of it, and a short rotation slides both
+//! home.
+//! The last 3x3 area or smaller is finished with an exact search.
+//!
+//! Every tile is moved with a breadth-first search over the positions of the
+//! tile and the blank, so the whole solve is polynomial in the board size.
+//! Far from optimal, but it always finishes.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::{self, Stats};
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Largest area left to the exact search.
+const EXACT_CELLS: usize = 9;
+
+struct Solver {
+    board: Vec<usize>,
+    len: usize,
+    blank: usize,
+    locked: Vec<bool>,
+    /// Cell the blank moved to, for every move.
+    moves: Vec<usize>,
+    stats: Stats,
+}
+
+impl Solver {
+    fn neighbours(&self, i: usize) -> Vec<usize> {
+        let (x, y) = (i / self.len, i % self.len);
+        let mut res = Vec::with_capacity(4);
+        if x > 0 {
+            res.push(i - self.len);
+        }
+        if x + 1 < self.len {
+            res.push(i + self.len);
+        }
+        if y > 0 {
+            res.push(i - 1);
+        }
+        if y + 1 < self.len {
+            res.push(i + 1);
+        }
+        res
+    }
+
+    fn slide(&mut self, to: usize) {
+        self.board.swap(self.blank, to);
+        self.blank = to;
+        self.moves.push(to);
+    }
+
+    fn position(&self, tile: usize) -> usize {
+        self.board.iter().position(|&v| v == tile).expect("tile should be on the board")
+    }
+
+    /// Walks the blank to `to` through unlocked cells.
+    fn move_blank(&mut self, to: usize) -> bool {
+        let cells = self.board.len();
+        let mut parent = vec![usize::MAX; cells];
+        let mut queue = VecDeque::new();
+        parent[self.blank] = self.blank;
+        queue.push_back(self.blank);
+        while let Some(i) = queue.pop_front() {
+            if i == to {
+                break;
+            }
+            for n in self.neighbours(i) {
+                if !self.locked[n] && parent[n] == usize::MAX {
+                    parent[n] = i;
+                    queue.push_back(n);
+                }
+            }
+        }
+        if parent[to] == usize::MAX {
+            return false;
+        }
+
+        let mut path = vec![to];
+        while parent[path[path.len() - 1]] != path[path.len() - 1] {
+            let p = parent[path[path.len() - 1]];
+            path.push(p);
+        }
+        for &i in path.iter().rev().skip(1) {
+            self.slide(i);
+        }
+        true
+    }
+
+    /// Brings `tile` to `target` through unlocked cells, searching over the
+    /// positions of both the tile and the blank.
+    fn place(&mut self, tile: usize, target: usize) -> bool {
+        let cells = self.board.len();
+        let start = self.position(tile) * cells + self.blank;
+        let mut parent = vec![usize::MAX; cells * cells];
+        let mut queue = VecDeque::new();
+        parent[start] = start;
+        queue.push_back(start);
+
+        let mut end = None;
+        while let Some(s) = queue.pop_front() {
+            let (t, b) = (s / cells, s % cells);
+            if t == target {
+                end = Some(s);
+                break;
+            }
+            for n in self.neighbours(b) {
+                if self.locked[n] {
+                    continue;
+                }
+                // Moving the blank onto the tile slides the tile back
+                let next = if n == t { b * cells + n } else { t * cells + n };
+                if parent[next] == usize::MAX {
+                    parent[next] = s;
+                    queue.push_back(next);
+                }
+            }
+        }
+        self.stats.total_states += queue.len();
+        let mut s = match end {
+            Some(s) => s,
+            None => return false,
+        };
+
+        let mut path = Vec::new();
+        while parent[s] != s {
+            path.push(s % cells);
+            s = parent[s];
+        }
+        for &b in path.iter().rev() {
+            self.slide(b);
+        }
+        true
+    }
+
+    /// Fills `cells`, a whole side of the remaining area ending on a corner,
+    /// with their goal tiles. `inward` is the offset from a cell of the side
+    /// to its neighbour inside the area.
+    fn fill_side(&mut self, cells: &[usize], inward: isize, goal: &[usize]) -> bool {
+        let l = cells.len();
+        for &c in &cells[..l - 2] {
+            if !self.place(goal[c], c) {
+                return false;
+            }
+            self.locked[c] = true;
+        }
+
+        let (a, b) = (cells[l - 2], cells[l - 1]);
+        if self.board[a] != goal[a] || self.board[b] != goal[b] {
+            let below = (a as isize + inward) as usize;
+            let far = (a as isize + 2 * inward) as usize;
+            // Parked away first, or it could end up stuck in the corner
+            if !self.place(goal[a], far) {
+                return false;
+            }
+            self.locked[far] = true;
+            if !self.place(goal[b], a) {
+                return false;
+            }
+            self.locked[a] = true;
+            self.locked[far] = false;
+            if !self.place(goal[a], below) {
+                return false;
+            }
+            self.locked[below] = true;
+            if !self.move_blank(b) {
+                return false;
+            }
+            self.locked[below] = false;
+            self.slide(a);
+            self.slide(below);
+        }
+        self.locked[a] = true;
+        self.locked[b] = true;
+        true
+    }
+
+    /// Breadth-first search restricted to `area`, every other cell being
+    /// already solved.
+    fn exact(&mut self, area: &[usize], goal: &[usize]) -> bool {
+        let state = area.iter().map(|&c| self.board[c]).collect::<Vec<_>>();
+        let target = area.iter().map(|&c| goal[c]).collect::<Vec<_>>();
+        let mut parent: HashMap<Vec<usize>, Vec<usize>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        parent.insert(state.clone(), state.clone());
+        queue.push_back(state);
+
+        while let Some(s) = queue.pop_front() {
+            if s == target {
+                break;
+            }
+            let blank = s.iter().position(|&v| v == 0).expect("area should hold the blank");
+            for (i, &c) in area.iter().enumerate() {
+                if !self.neighbours(area[blank]).contains(&c) {
+                    continue;
+                }
+                let mut next = s.clone();
+                next.swap(blank, i);
+                if !parent.contains_key(&next) {
+                    parent.insert(next.clone(), s.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        self.stats.total_states += parent.len();
+        self.stats.max_states = cmp::max(self.stats.max_states, parent.len());
+        if !parent.contains_key(&target) {
+            return false;
+        }
+
+        let mut path = vec![target];
+        while parent[&path[path.len() - 1]] != path[path.len() - 1] {
+            let p = parent[&path[path.len() - 1]].clone();
+            path.push(p);
+        }
+        for s in path.iter().rev().skip(1) {
+            let blank = s.iter().position(|&v| v == 0).expect("area should hold the blank");
+            self.slide(area[blank]);
+        }
+        true
+    }
+}
+
+pub fn solve(n: Node, h: &Heuristic) -> Option<(Arc<Node>, Stats)> {
+    let len = n.len;
+    let goal = Node::goal(len).board;
+    let cells = len * len;
+    let mut s = Solver {
+        blank: n.board.iter().position(|&v| v == 0)?,
+        board: n.board.clone(),
+        len,
+        locked: vec![false; cells],
+        moves: Vec::new(),
+        stats: Stats {
+            total_states: 0,
+            max_states: cells * cells,
+        },
+    };
+
+    let (mut top, mut bottom, mut left, mut right) = (0, len - 1, 0, len - 1);
+    let mut side = 0;
+    while (bottom - top + 1) * (right - left + 1) > EXACT_CELLS {
+        let (line, inward) = match side % 4 {
+            0 => ((left..=right).map(|c| top * len + c).collect::<Vec<_>>(), len as isize),
+            1 => ((top..=bottom).map(|r| r * len + right).collect(), -1),
+            2 => ((left..=right).rev().map(|c| bottom * len + c).collect(), -(len as isize)),
+            _ => ((top..=bottom).rev().map(|r| r * len + left).collect(), 1),
+        };
+        if !s.fill_side(&line, inward, &goal) {
+            return None;
+        }
+        match side % 4 {
+            0 => top += 1,
+            1 => right -= 1,
+            2 => bottom -= 1,
+            _ => left += 1,
+        }
+        side += 1;
+    }
+
+    let area = (top..=bottom).flat_map(|r| (left..=right).map(move |c| r * len + c)).collect::<Vec<_>>();
+    if !s.exact(&area, &goal) {
+        return None;
+    }
+
+    let mut board = n.board;
+    let mut blank = board.iter().position(|&v| v == 0)?;
+    let mut boards = vec![board.clone()];
+    for &m in &s.moves {
+        board.swap(blank, m);
+        blank = m;
+        boards.push(board.clone());
+    }
+    solver::chain(boards, len, h).map(|node| (node, s.stats))
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::Heuristic;
+    use node::Node;
+
+    #[test]
+    fn constructive_solves_any_size() {
+        let h = Heuristic::parse("tiles").unwrap();
+        for &size in &[2, 3, 4, 5, 8, 11] {
+            let n = Node::random(size, 1000, true);
+            let (found, _) = super::solve(n.clone(), &h).expect("solvable board should be solved");
+            assert!(*found == Node::goal(size));
+
+            let path = found.parents().collect::<Vec<_>>();
+            assert!(*path[path.len() - 1] == n);
+            for pair in path.windows(2) {
+                let moved = pair[0].board.iter().zip(pair[1].board.iter()).filter(|&(a, b)| a != b).count();
+                assert!(moved == 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! External-memory breadth-first heuristic search. Every layer of the search
+//! is a file of sorted boards, one byte per cell. Children of a layer are
+//! collected in memory up to the budget, then sorted and spilled to a run
+//! file. The runs are merged into the next layer, dropping duplicates and the
+//! boards of the previous layer on the way: moves alternate the parity of the
+//! blank, so a child can only already be in the layer before its parent's.
+//!
+//! Children whose f exceeds the bound are pruned, and the bound is raised to
+//! the smallest pruned f until the goal is reached. The path is rebuilt by
+//! looking up a neighbour of each board in the layer before it.
+
+use enumerate;
+use heuristics::Heuristic;
+use node::Node;
+use solver::{self, Stats};
+use std::cmp::{self, Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+
+pub struct Config {
+    /// Bytes of boards kept in memory before they are spilled to a run file.
+    pub mem_budget: usize,
+    pub scratch_dir: PathBuf,
+}
+
+/// Directory of the search files, removed with everything in it on drop.
+struct Scratch {
+    dir: PathBuf,
+}
+
+impl Scratch {
+    fn create(parent: &Path) -> io::Result<Scratch> {
+        let dir = parent.join(format!("n-puzzle-{}", process::id()));
+        fs::create_dir_all(&dir)?;
+        Ok(Scratch { dir })
+    }
+
+    fn layer(&self, depth: usize) -> PathBuf {
+        self.dir.join(format!("layer_{}.bin", depth))
+    }
+
+    fn run(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("run_{}.bin", index))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn encode(board: &[usize]) -> Vec<u8> {
+    board.iter().map(|&v| v as u8).collect()
+}
+
+fn decode(record: &[u8]) -> Vec<usize> {
+    record.iter().map(|&v| v as usize).collect()
+}
+
+/// Sequential reader of the fixed size records of a file.
+struct Records {
+    reader: BufReader<File>,
+    size: usize,
+}
+
+impl Records {
+    fn open(path: &Path, size: usize) -> io::Result<Records> {
+        Ok(Records {
+            reader: BufReader::new(File::open(path)?),
+            size,
+        })
+    }
+}
+
+impl Iterator for Records {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        let mut record = vec![0; self.size];
+        match self.reader.read_exact(&mut record) {
+            Ok(()) => Some(Ok(record)),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+fn write_run(path: &Path, buffer: &mut Vec<Vec<u8>>) -> io::Result<()> {
+    buffer.sort_unstable();
+    buffer.dedup();
+    let mut w = BufWriter::new(File::create(path)?);
+    for record in buffer.iter() {
+        w.write_all(record)?;
+    }
+    buffer.clear();
+    w.flush()
+}
+
+/// Binary search of a sorted layer file.
+fn contains(path: &Path, size: usize, record: &[u8]) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    let (mut low, mut high) = (0, file.metadata()?.len() / size as u64);
+    let mut buf = vec![0; size];
+    while low < high {
+        let mid = (low + high) / 2;
+        file.seek(SeekFrom::Start(mid * size as u64))?;
+        file.read_exact(&mut buf)?;
+        match buf.as_slice().cmp(record) {
+            Ordering::Equal => return Ok(true),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+        }
+    }
+    Ok(false)
+}
+
+struct Search<'a> {
+    len: usize,
+    h: &'a Heuristic,
+    /// Number of records the memory budget holds.
+    capacity: usize,
+    scratch: Scratch,
+    stats: Stats,
+}
+
+impl<'a> Search<'a> {
+    /// Writes the children of the layer at `depth` that fit in `bound` to
+    /// sorted run files. Returns the runs and the smallest pruned f.
+    fn expand(&mut self, depth: usize, bound: usize) -> io::Result<(Vec<PathBuf>, Option<usize>)> {
+        let size = self.len * self.len;
+        let mut runs = Vec::new();
+        let mut pruned: Option<usize> = None;
+        let mut buffer: Vec<Vec<u8>> = Vec::new();
+
+        for record in Records::open(&self.scratch.layer(depth), size)? {
+            let board = decode(&record?);
+            for child in enumerate::neighbours(&board, self.len) {
+                let node = Node {
+                    board: child,
+                    len: self.len,
+                    cost: depth + 1,
+                    heuristic: 0,
+                    parents: None,
+                };
+                let f = node.cost + self.h.eval(&node);
+                if f > bound {
+                    pruned = Some(pruned.map_or(f, |p| cmp::min(p, f)));
+                    continue;
+                }
+                buffer.push(encode(&node.board));
+                if buffer.len() >= self.capacity {
+                    self.stats.max_states = cmp::max(self.stats.max_states, buffer.len());
+                    let path = self.scratch.run(runs.len());
+                    write_run(&path, &mut buffer)?;
+                    runs.push(path);
+                }
+            }
+        }
+        if !buffer.is_empty() {
+            self.stats.max_states = cmp::max(self.stats.max_states, buffer.len());
+            let path = self.scratch.run(runs.len());
+            write_run(&path, &mut buffer)?;
+            runs.push(path);
+        }
+
+        Ok((runs, pruned))
+    }
+
+    /// Merges the runs into the layer at `depth`, without duplicates nor the
+    /// boards of the layer before. Returns the number of boards written and
+    /// whether `goal` is one of them.
+    fn merge(&mut self, runs: &[PathBuf], depth: usize, goal: &[u8]) -> io::Result<(usize, bool)> {
+        let size = self.len * self.len;
+        let mut readers = runs.iter().map(|p| Records::open(p, size)).collect::<io::Result<Vec<_>>>()?;
+        let mut heap = BinaryHeap::new();
+        for (i, r) in readers.iter_mut().enumerate() {
+            if let Some(record) = r.next() {
+                heap.push(Reverse((record?, i)));
+            }
+        }
+        let mut previous = match depth {
+            0 | 1 => None,
+            d => Some(Records::open(&self.scratch.layer(d - 2), size)?),
+        };
+        let mut old = match previous.as_mut().and_then(|p| p.next()) {
+            Some(r) => Some(r?),
+            None => None,
+        };
+
+        let mut w = BufWriter::new(File::create(self.scratch.layer(depth))?);
+        let mut last: Option<Vec<u8>> = None;
+        let (mut count, mut found) = (0, false);
+        while let Some(Reverse((record, i))) = heap.pop() {
+            if let Some(next) = readers[i].next() {
+                heap.push(Reverse((next?, i)));
+            }
+            if last.as_ref() == Some(&record) {
+                continue;
+            }
+            while old.as_ref().map(|o| *o < record).unwrap_or(false) {
+                old = match previous.as_mut().and_then(|p| p.next()) {
+                    Some(r) => Some(r?),
+                    None => None,
+                };
+            }
+            if old.as_ref() != Some(&record) {
+                w.write_all(&record)?;
+                count += 1;
+                found |= *record == *goal;
+            }
+            last = Some(record);
+        }
+        w.flush()?;
+
+        Ok((count, found))
+    }
+
+    /// Boards from the start to `goal`, which was reached at `depth`.
+    fn path(&self, goal: Vec<usize>, depth: usize) -> io::Result<Vec<Vec<usize>>> {
+        let size = self.len * self.len;
+        let mut boards = vec![goal];
+        for d in (0..depth).rev() {
+            let mut previous = None;
+            for n in enumerate::neighbours(&boards[boards.len() - 1], self.len) {
+                if contains(&self.scratch.layer(d), size, &encode(&n))? {
+                    previous = Some(n);
+                    break;
+                }
+            }
+            let previous = previous.ok_or_else(|| io::Error::other(format!("layer {} is corrupted", d)))?;
+            boards.push(previous);
+        }
+
+        boards.reverse();
+        Ok(boards)
+    }
+}
+
+/// Optimal as long as the heuristic is admissible, whatever the budget.
+pub fn search(n: Node, h: &Heuristic, config: &Config) -> io::Result<Option<(Arc<Node>, Stats)>> {
+    let len = n.len;
+    if len * len > 256 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "external search stores cells on one byte, boards up to 16x16"));
+    }
+    let size = len * len;
+    let goal = Node::goal(len).board;
+    let record_cost = size + mem::size_of::<Vec<u8>>();
+
+    let mut search = Search {
+        len,
+        h,
+        capacity: cmp::max(1, config.mem_budget / record_cost),
+        scratch: Scratch::create(&config.scratch_dir)?,
+        stats: Stats {
+            total_states: 1,
+            max_states: 1,
+        },
+    };
+
+    let mut bound = h.eval(&n);
+    loop {
+        write_run(&search.scratch.layer(0), &mut vec![encode(&n.board)])?;
+        let mut next_bound: Option<usize> = None;
+        let mut depth = 0;
+        let mut found = n.board == goal;
+
+        while !found {
+            let (runs, pruned) = search.expand(depth, bound)?;
+            if let Some(p) = pruned {
+                next_bound = Some(next_bound.map_or(p, |b| cmp::min(b, p)));
+            }
+            let (count, reached) = search.merge(&runs, depth + 1, &encode(&goal))?;
+            for run in runs {
+                fs::remove_file(run)?;
+            }
+            if count == 0 {
+                break;
+            }
+            search.stats.total_states += count;
+            depth += 1;
+            found = reached;
+        }
+
+        if found {
+            let boards = search.path(goal, depth)?;
+            return Ok(solver::chain(boards, len, h).map(|node| (node, search.stats)));
+        }
+        match next_bound {
+            Some(b) => bound = b,
+            None => return Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::Heuristic;
+    use solver::fixture;
+    use std::env;
+    use std::mem;
+    use std::process;
+
+    #[test]
+    fn external_spills_with_tiny_budget() {
+        let h = Heuristic::Base("dijkstra", fixture::dijkstra);
+        let config = super::Config {
+            mem_budget: 1000,
+            scratch_dir: env::temp_dir().join("n-puzzle-external-test"),
+        };
+        let capacity = config.mem_budget / (9 + mem::size_of::<Vec<u8>>());
+
+        let mut seen = 0;
+        for (n, d) in fixture::boards() {
+            let (end, stats) = super::search(n.clone(), &h, &config).unwrap().expect("reachable board should be solved");
+            fixture::assert_path(&n, &end, d);
+            assert!(stats.max_states <= capacity);
+            seen = seen.max(stats.total_states);
+        }
+
+        // The deepest board sees far more boards than the budget holds, so
+        // they went through run files, removed once the search is over.
+        assert!(seen > 100 * capacity);
+        assert!(!config.scratch_dir.join(format!("n-puzzle-{}", process::id())).exists());
+    }
+}
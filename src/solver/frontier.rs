@@ -0,0 +1,197 @@
+//! Frontier A*: expanded boards are dropped instead of being kept in a closed
+//! set. Every open board remembers the moves leading back to neighbours that
+//! were already expanded, so those are never generated again. Without parent
+//! pointers the path is rebuilt by divide and conquer: every node carries the
+//! board its path went through halfway, and both halves are searched again.
+//!
+//! Dropping expanded boards is only safe when the heuristic is consistent, an
+//! inconsistent one still finds a path but it may not be optimal.
+
+use heuristics::Heuristic;
+use node::{Direction, Node};
+use solver::{self, Stats};
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+use verify::{self, Move};
+
+const MOVES: [Direction; 4] = [Direction::North, Direction::South, Direction::East, Direction::West];
+
+fn bit(d: Direction) -> u8 {
+    match d {
+        Direction::North => 1,
+        Direction::South => 2,
+        Direction::East => 4,
+        Direction::West => 8,
+    }
+}
+
+struct Entry {
+    g: usize,
+    /// Moves leading to neighbours that were already expanded.
+    used: u8,
+    /// Board the path went through at the relay depth.
+    relay: Option<Arc<Vec<usize>>>,
+}
+
+enum Estimate<'a> {
+    Goal(&'a Heuristic),
+    /// Manhattan distance of the tiles, blank excluded, to an arbitrary board
+    /// given as the position of every tile. Admissible and consistent.
+    Board(Vec<usize>),
+}
+
+impl<'a> Estimate<'a> {
+    fn new(target: &[usize], len: usize, h: &'a Heuristic) -> Estimate<'a> {
+        if *target == *Node::goal(len).board {
+            return Estimate::Goal(h);
+        }
+        let mut positions = vec![0; target.len()];
+        for (i, &v) in target.iter().enumerate() {
+            positions[v] = i;
+        }
+        Estimate::Board(positions)
+    }
+
+    fn eval(&self, board: &[usize], len: usize) -> usize {
+        match *self {
+            Estimate::Goal(h) => h.eval(&Node {
+                board: board.to_vec(),
+                len,
+                cost: 0,
+                heuristic: 0,
+                parents: None,
+            }),
+            Estimate::Board(ref positions) => board.iter().enumerate()
+                .filter(|&(_, &v)| v != 0)
+                .map(|(i, &v)| {
+                    let (x, y) = (i / len, i % len);
+                    let (tx, ty) = (positions[v] / len, positions[v] % len);
+                    x.abs_diff(tx) + y.abs_diff(ty)
+                })
+                .sum(),
+        }
+    }
+}
+
+/// Frontier A* from `start` to `target`. Returns the cost of the path and the
+/// board it went through at `relay_depth`, when it is at least that long.
+fn search(start: &[usize],
+          target: &[usize],
+          len: usize,
+          estimate: &Estimate,
+          relay_depth: usize,
+          stats: &mut Stats) -> Option<(usize, Option<Arc<Vec<usize>>>)> {
+    let mut open: HashMap<Vec<usize>, Entry> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    open.insert(start.to_vec(), Entry { g: 0, used: 0, relay: None });
+    queue.push(Reverse((estimate.eval(start, len), Reverse(0), start.to_vec())));
+
+    while let Some(Reverse((_, Reverse(g), board))) = queue.pop() {
+        match open.get(&board) {
+            Some(e) if e.g == g => {}
+            // Stale entry, or a board that was already expanded
+            _ => continue,
+        }
+        let entry = open.remove(&board).expect("entry was just found");
+        if *board == *target {
+            return Some((g, entry.relay));
+        }
+
+        for &d in MOVES.iter() {
+            if entry.used & bit(d) != 0 {
+                continue;
+            }
+            let mut child = board.clone();
+            if verify::apply(&mut child, len, Move::Blank(d)).is_err() {
+                continue;
+            }
+
+            let cost = g + 1;
+            let back = bit(d.opposite());
+            let relay = if cost == relay_depth {
+                Some(Arc::new(child.clone()))
+            } else {
+                entry.relay.clone()
+            };
+            let pushed = match open.get_mut(&child) {
+                Some(e) => {
+                    e.used |= back;
+                    if cost < e.g {
+                        e.g = cost;
+                        e.relay = relay;
+                    }
+                    e.g == cost
+                }
+                None => {
+                    open.insert(child.clone(), Entry { g: cost, used: back, relay });
+                    true
+                }
+            };
+            if pushed {
+                stats.total_states += 1;
+                queue.push(Reverse((cost + estimate.eval(&child, len), Reverse(cost), child)));
+            }
+        }
+        stats.max_states = cmp::max(stats.max_states, open.len());
+    }
+
+    None
+}
+
+/// Boards of a path from `start` to `target`, both included, rebuilt by
+/// splitting it on its relay board.
+fn path(start: &[usize], target: &[usize], len: usize, h: &Heuristic, stats: &mut Stats) -> Option<Vec<Vec<usize>>> {
+    let estimate = Estimate::new(target, len, h);
+    let depth = cmp::max(1, estimate.eval(start, len) / 2);
+    let (cost, relay) = search(start, target, len, &estimate, depth, stats)?;
+    if cost <= 1 {
+        let mut boards = vec![start.to_vec()];
+        if cost == 1 {
+            boards.push(target.to_vec());
+        }
+        return Some(boards);
+    }
+
+    let relay = match relay {
+        Some(r) if depth < cost => r,
+        // The estimate overshot the real distance, now it is known
+        _ => search(start, target, len, &estimate, cost / 2, stats)?.1?,
+    };
+    let mut first = path(start, &relay, len, h, stats)?;
+    let second = path(&relay, target, len, h, stats)?;
+    first.extend(second.into_iter().skip(1));
+    Some(first)
+}
+
+pub fn astar(n: Node, h: &Heuristic) -> Option<(Arc<Node>, Stats)> {
+    let len = n.len;
+    let goal = Node::goal(len);
+    let mut stats = Stats {
+        total_states: 1,
+        max_states: 0,
+    };
+    let boards = path(&n.board, &goal.board, len, h, &mut stats)?;
+
+    solver::chain(boards, len, h).map(|node| (node, stats))
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::{self, Heuristic};
+    use solver::fixture;
+
+    #[test]
+    fn frontier_finds_optimal_path() {
+        // Without a heuristic the relay sits at depth 1, with the exact one
+        // halfway, so both ends of the divide and conquer are exercised.
+        let zero = Heuristic::Base("dijkstra", fixture::dijkstra);
+        let exact = Heuristic::Base("exact", heuristics::eval_exact);
+        for (n, d) in fixture::boards() {
+            for h in &[&zero, &exact] {
+                let (end, _) = super::astar(n.clone(), h).expect("reachable board should be solved");
+                fixture::assert_path(&n, &end, d);
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Hash-distributed A*: every board is owned by exactly one worker, chosen by
+//! hashing the board, and only that worker keeps it in its open and closed
+//! sets. Generated nodes are sent to their owner over a channel.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::Stats;
+use std::cmp::{self, Reverse};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+struct Shared {
+    /// Nodes that are either in flight in a channel or waiting in an open
+    /// list. Children are counted before their parent is released, so the
+    /// counter only reaches zero once the whole search space is exhausted.
+    work: AtomicUsize,
+    /// Cost of the best solution found so far, `usize::MAX` when none.
+    bound: AtomicUsize,
+    best: Mutex<Option<Arc<Node>>>,
+    total_states: AtomicUsize,
+    max_states: AtomicUsize,
+}
+
+fn owner(board: &[usize], workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    board.hash(&mut hasher);
+    (hasher.finish() % workers as u64) as usize
+}
+
+fn worker(id: usize,
+          rx: Receiver<Arc<Node>>,
+          peers: Vec<Sender<Arc<Node>>>,
+          shared: Arc<Shared>,
+          goal: Arc<Node>,
+          h: Heuristic) {
+    // Ordered on f explicitly: `Ord for Node` sorts on h alone under the
+    // greedy feature, which would break the bound cut-off below.
+    let mut open: BinaryHeap<(Reverse<usize>, Arc<Node>)> = BinaryHeap::new();
+    let mut best_cost: HashMap<Vec<usize>, usize> = HashMap::new();
+    let mut max_open = 0_usize;
+
+    let accept = |node: Arc<Node>, open: &mut BinaryHeap<(Reverse<usize>, Arc<Node>)>, best_cost: &mut HashMap<Vec<usize>, usize>| {
+        let better = best_cost.get(&node.board).map(|&c| node.cost < c).unwrap_or(true);
+        if better {
+            best_cost.insert(node.board.clone(), node.cost);
+            open.push((Reverse(node.cost + node.heuristic), node));
+        } else {
+            shared.work.fetch_sub(1, Ordering::SeqCst);
+        }
+    };
+
+    loop {
+        while let Ok(node) = rx.try_recv() {
+            accept(node, &mut open, &mut best_cost);
+        }
+        max_open = cmp::max(max_open, open.len());
+
+        let bound = shared.bound.load(Ordering::SeqCst);
+        let node = match open.pop() {
+            Some((_, n)) => n,
+            None => {
+                if shared.work.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                if let Ok(node) = rx.recv_timeout(Duration::from_millis(1)) {
+                    accept(node, &mut open, &mut best_cost);
+                }
+                continue;
+            }
+        };
+
+        if node.cost + node.heuristic >= bound {
+            // The heap is ordered on f, nothing left here can beat the bound.
+            shared.work.fetch_sub(open.len() + 1, Ordering::SeqCst);
+            open.clear();
+            continue;
+        }
+
+        if best_cost.get(&node.board).map(|&c| c < node.cost).unwrap_or(false) {
+            shared.work.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+
+        if *node == *goal {
+            let mut best = shared.best.lock().expect("hda incumbent poisoned");
+            if node.cost < shared.bound.load(Ordering::SeqCst) {
+                shared.bound.store(node.cost, Ordering::SeqCst);
+                *best = Some(node.clone());
+            }
+        } else {
+            for neighbour in Node::get_next_steps(&node, &h) {
+                if neighbour.cost + neighbour.heuristic >= bound {
+                    continue;
+                }
+                shared.work.fetch_add(1, Ordering::SeqCst);
+                shared.total_states.fetch_add(1, Ordering::SeqCst);
+                let target = owner(&neighbour.board, peers.len());
+                let neighbour = Arc::new(neighbour);
+                if target == id {
+                    accept(neighbour, &mut open, &mut best_cost);
+                } else if peers[target].send(neighbour).is_err() {
+                    shared.work.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+        shared.work.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    shared.max_states.fetch_add(max_open, Ordering::SeqCst);
+}
+
+/// Parallel A* over `jobs` threads. Nodes are reopened when reached through
+/// a cheaper path, and the search only stops once no open node anywhere can
+/// improve the incumbent, so the result is as optimal as the sequential one.
+pub fn astar(n: Node, h: &Heuristic, jobs: usize) -> Option<(Arc<Node>, Stats)> {
+    let jobs = cmp::max(jobs, 1);
+    let goal = Arc::new(Node::goal(n.len));
+    let shared = Arc::new(Shared {
+        work: AtomicUsize::new(1),
+        bound: AtomicUsize::new(usize::MAX),
+        best: Mutex::new(None),
+        total_states: AtomicUsize::new(1),
+        max_states: AtomicUsize::new(0),
+    });
+
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..jobs).map(|_| mpsc::channel()).unzip();
+    let start = owner(&n.board, jobs);
+    senders[start].send(Arc::new(n)).expect("hda worker channel closed");
+
+    let handles = receivers.into_iter().enumerate().map(|(id, rx)| {
+        let peers = senders.clone();
+        let shared = shared.clone();
+        let goal = goal.clone();
+        let h = h.clone();
+        thread::spawn(move || worker(id, rx, peers, shared, goal, h))
+    }).collect::<Vec<_>>();
+    drop(senders);
+
+    for handle in handles {
+        handle.join().expect("hda worker panicked");
+    }
+
+    let best = shared.best.lock().expect("hda incumbent poisoned").take();
+    best.map(|node| {
+        let stats = Stats {
+            total_states: shared.total_states.load(Ordering::SeqCst),
+            max_states: shared.max_states.load(Ordering::SeqCst),
+        };
+        (node, stats)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::Heuristic;
+    use solver::fixture;
+
+    #[test]
+    fn hda_matches_sequential_astar() {
+        let h = Heuristic::Base("dijkstra", fixture::dijkstra);
+        for (n, d) in fixture::boards() {
+            for jobs in 1..5 {
+                let (end, _) = super::astar(n.clone(), &h, jobs).expect("reachable board should be solved");
+                fixture::assert_path(&n, &end, d);
+            }
+        }
+    }
+}
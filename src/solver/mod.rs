@@ -0,0 +1,280 @@
+pub mod hda;
+pub mod anytime;
+pub mod beam;
+pub mod bidirectional;
+pub mod constructive;
+pub mod external;
+pub mod frontier;
+pub mod realtime;
+pub mod open;
+
+use heuristics::Heuristic;
+use node::Node;
+use self::open::{OpenList, TieBreak};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub enum Algorithm {
+    AStar,
+    Bidirectional,
+    Frontier,
+    External,
+    Lrta,
+    Anytime,
+    Beam,
+    Constructive,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "astar" => Ok(Algorithm::AStar),
+            "bidirectional" => Ok(Algorithm::Bidirectional),
+            "frontier" => Ok(Algorithm::Frontier),
+            "external" => Ok(Algorithm::External),
+            "lrta" => Ok(Algorithm::Lrta),
+            "anytime" => Ok(Algorithm::Anytime),
+            "beam" => Ok(Algorithm::Beam),
+            "constructive" => Ok(Algorithm::Constructive),
+            a => Err(format!("Invalid value for algorithm: {}, possible values are: \
+                 \n\tastar: A* (parallel with --jobs) \
+                 \n\tbidirectional: Bidirectional breadth-first search \
+                 \n\tfrontier: A* without a closed set, needs a consistent heuristic \
+                 \n\texternal: Breadth-first heuristic search on disk (--mem-budget, --scratch-dir) \
+                 \n\tlrta: Real-time search with learning (--move-nodes, --move-time, --trials) \
+                 \n\tanytime: Anytime weighted A*, prints every improved solution (--weight) \
+                 \n\tbeam: Beam search, keeps the best nodes of every depth (--beam-width) \
+                 \n\tconstructive: Side by side placement as done by hand, any size", a)),
+        }
+    }
+}
+
+pub struct Stats {
+    pub total_states: usize,
+    pub max_states: usize,
+}
+
+/// What happened to a neighbour generated while expanding a node.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Pushed,
+    /// Pushed again after being expanded, through a cheaper path.
+    Reopened,
+    /// Dropped because the board was already expanded.
+    Closed,
+    /// Dropped because the board already waits in the open set.
+    Opened,
+}
+
+/// Hooks into the A* loop, used to dump or trace the search.
+pub trait Observer {
+    fn expand(&mut self, _node: &Node, _open: usize, _closed: usize) {}
+    fn generate(&mut self, _parent: &Node, _child: &Node, _outcome: Outcome) {}
+}
+
+impl Observer for () {}
+
+impl<T: Observer> Observer for Option<T> {
+    fn expand(&mut self, node: &Node, open: usize, closed: usize) {
+        if let Some(ref mut o) = *self {
+            o.expand(node, open, closed);
+        }
+    }
+
+    fn generate(&mut self, parent: &Node, child: &Node, outcome: Outcome) {
+        if let Some(ref mut o) = *self {
+            o.generate(parent, child, outcome);
+        }
+    }
+}
+
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn expand(&mut self, node: &Node, open: usize, closed: usize) {
+        self.0.expand(node, open, closed);
+        self.1.expand(node, open, closed);
+    }
+
+    fn generate(&mut self, parent: &Node, child: &Node, outcome: Outcome) {
+        self.0.generate(parent, child, outcome);
+        self.1.generate(parent, child, outcome);
+    }
+}
+
+/// Links the boards of a path into nodes, for solvers that do not keep parent
+/// pointers while searching.
+pub fn chain(boards: Vec<Vec<usize>>, len: usize, h: &Heuristic) -> Option<Arc<Node>> {
+    let mut current: Option<Arc<Node>> = None;
+    for (cost, board) in boards.into_iter().enumerate() {
+        let mut node = Node {
+            board,
+            len,
+            cost,
+            heuristic: 0,
+            parents: current.take(),
+        };
+        node.heuristic = h.eval(&node);
+        current = Some(Arc::new(node));
+    }
+
+    current
+}
+
+pub fn astar(n: Node, h: &Heuristic) -> Option<(Arc<Node>, Stats)> {
+    astar_observed(n, h, Box::new(open::Heap::new(TieBreak::Arbitrary)), &mut ())
+}
+
+/// A* with lazy deletion: a cheaper path to a board pushes a new entry and
+/// records its g in `opened`, older entries are skipped when popped. Closed
+/// boards are reopened when reached through a cheaper path, which keeps the
+/// result optimal under admissible but inconsistent heuristics.
+pub fn astar_observed(mut n: Node, h: &Heuristic, mut open: Box<dyn OpenList>, observer: &mut dyn Observer) -> Option<(Arc<Node>, Stats)> {
+    let goal: Node = Node::goal(n.len);
+    n.heuristic = h.eval(&n);
+
+    let r = Arc::new(n);
+    let mut opened: HashMap<Arc<Node>, usize> = HashMap::new();
+    let mut closed: HashMap<Arc<Node>, usize> = HashMap::new();
+
+    opened.insert(r.clone(), 0);
+    open.push(r);
+    let mut total_states = 1_usize;
+    let mut max_states = 0_usize;
+
+    while let Some(node) = open.pop() {
+        if opened.get(node.as_ref()) != Some(&node.cost) {
+            // Stale entry, the board was reached again with a lower cost
+            continue;
+        }
+
+        let sum = opened.len();
+        if sum > max_states {
+            max_states = sum;
+        }
+        observer.expand(&node, opened.len() - 1, closed.len());
+        if *(node.as_ref()) == goal {
+            let stats = Stats {
+                total_states,
+                max_states,
+            };
+            return Some((node, stats));
+        }
+
+        let r = node;
+        opened.remove(r.as_ref());
+        closed.insert(r.clone(), r.cost);
+
+        for neighbour in Node::get_next_steps(&r, h) {
+            let outcome = if closed.get(&neighbour).map(|&g| g <= neighbour.cost).unwrap_or(false) {
+                Outcome::Closed
+            } else if opened.get(&neighbour).map(|&g| g <= neighbour.cost).unwrap_or(false) {
+                Outcome::Opened
+            } else if closed.remove(&neighbour).is_some() {
+                Outcome::Reopened
+            } else {
+                Outcome::Pushed
+            };
+            observer.generate(&r, &neighbour, outcome);
+
+            if outcome == Outcome::Pushed || outcome == Outcome::Reopened {
+                total_states += 1;
+                let arc = Arc::new(neighbour);
+                opened.insert(arc.clone(), arc.cost);
+                open.push(arc);
+            }
+        }
+    }
+
+    None
+}
+
+/// Boards and checks shared by the tests of the solvers.
+#[cfg(test)]
+pub mod fixture {
+    use heuristics;
+    use node::Node;
+
+    /// Uniform cost search when used as a heuristic.
+    pub fn dijkstra(_: &Node) -> usize {
+        0
+    }
+
+    /// 3x3 boards from solved to deep, with their optimal distance.
+    pub fn boards() -> Vec<(Node, usize)> {
+        let maps = [
+            "3\n1 2 3\n8 0 4\n7 6 5\n",
+            "3\n1 2 3\n8 4 0\n7 6 5\n",
+            "3\n2 8 3\n1 6 4\n7 0 5\n",
+            "3\n8 1 3\n0 2 4\n7 6 5\n",
+            "3\n0 4 3\n8 1 7\n2 5 6\n",
+        ];
+        maps.iter().map(|map| {
+            let n: Node = map.parse().expect("Map should be valid");
+            let d = heuristics::eval_exact(&n);
+            (n, d)
+        }).collect()
+    }
+
+    /// Checks that `end` is the goal, reached from `start` in `cost` moves.
+    pub fn assert_path(start: &Node, end: &Node, cost: usize) {
+        assert!(*end == Node::goal(start.len));
+        assert!(end.cost == cost);
+
+        let path = end.parents().collect::<Vec<_>>();
+        assert!(path.len() == cost + 1);
+        assert!(path[path.len() - 1] == start);
+        for pair in path.windows(2) {
+            let moved = pair[0].board.iter().zip(pair[1].board.iter()).filter(|&(a, b)| a != b).count();
+            assert!(moved == 2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::open::{self, Queue, TieBreak};
+    use super::{astar, astar_observed};
+    use enumerate::DistanceTable;
+    use heuristics::{self, Heuristic};
+    use node::Node;
+
+    /// Exact on half of the boards and 0 elsewhere: admissible, but far from
+    /// consistent, so closed boards have to be reopened.
+    fn patchy(n: &Node) -> usize {
+        if n.board[0].is_multiple_of(2) {
+            heuristics::eval_exact(n)
+        } else {
+            0
+        }
+    }
+
+    #[test]
+    fn astar_is_optimal_on_known_distances() {
+        let table = DistanceTable::build(3);
+        let mut corpus = Vec::new();
+        let mut i = 0;
+        table.for_each(|board, d| {
+            if i % 15013 == 0 {
+                corpus.push((board.to_vec(), d));
+            }
+            i += 1;
+        });
+
+        let h = Heuristic::Base("patchy", patchy);
+        for (board, d) in corpus {
+            let n = Node {
+                board,
+                len: 3,
+                cost: 0,
+                heuristic: 0,
+                parents: None,
+            };
+            let buckets = open::build(Queue::Buckets, TieBreak::HighG);
+            let (end, _) = astar_observed(n.clone(), &h, buckets, &mut ()).expect("reachable board should be solved");
+            assert!(end.cost == d);
+            let (end, _) = astar(n, &h).expect("reachable board should be solved");
+            assert!(end.cost == d);
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Open lists of the A* loop. `Ord for Node` gives the main priority, ties
+//! between equal nodes are broken here according to a `TieBreak`.
+
+use node::Node;
+use rand::{self, Rng, XorShiftRng};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TieBreak {
+    /// Whatever order the heap happens to produce.
+    Arbitrary,
+    HighG,
+    LowH,
+    Fifo,
+    Lifo,
+    Random,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "none" => Ok(TieBreak::Arbitrary),
+            "high-g" => Ok(TieBreak::HighG),
+            "low-h" => Ok(TieBreak::LowH),
+            "fifo" => Ok(TieBreak::Fifo),
+            "lifo" => Ok(TieBreak::Lifo),
+            "random" => Ok(TieBreak::Random),
+            t => Err(format!("Invalid value for tie-break: {}, possible values are: \
+                 \n\thigh-g: Deepest node first \
+                 \n\tlow-h: Node closest to the goal first \
+                 \n\tfifo: Oldest node first \
+                 \n\tlifo: Newest node first \
+                 \n\trandom: Random order", t)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Queue {
+    Heap,
+    Buckets,
+}
+
+impl FromStr for Queue {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_ref() {
+            "heap" => Ok(Queue::Heap),
+            "buckets" => Ok(Queue::Buckets),
+            q => Err(format!("Invalid value for open-list: {}, possible values are: \
+                 \n\theap: Binary heap \
+                 \n\tbuckets: Array of buckets indexed by f and g", q)),
+        }
+    }
+}
+
+pub trait OpenList {
+    fn push(&mut self, node: Arc<Node>);
+    fn pop(&mut self) -> Option<Arc<Node>>;
+}
+
+pub fn build(queue: Queue, tie_break: TieBreak) -> Box<dyn OpenList> {
+    match queue {
+        Queue::Heap => Box::new(Heap::new(tie_break)),
+        Queue::Buckets => Box::new(Buckets::new(tie_break)),
+    }
+}
+
+/// The value `Ord for Node` sorts on, smallest first.
+#[cfg(feature = "greedy")]
+fn priority(n: &Node) -> usize {
+    n.heuristic
+}
+
+#[cfg(feature = "uniform")]
+fn priority(n: &Node) -> usize {
+    n.cost + n.heuristic
+}
+
+struct Entry {
+    node: Arc<Node>,
+    /// Secondary key, the greater the sooner the node is popped.
+    tie: u64,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        self.node.cmp(&other.node).then(self.tie.cmp(&other.tie))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct Heap {
+    heap: BinaryHeap<Entry>,
+    tie_break: TieBreak,
+    pushed: u64,
+    rng: XorShiftRng,
+}
+
+impl Heap {
+    pub fn new(tie_break: TieBreak) -> Heap {
+        Heap {
+            heap: BinaryHeap::new(),
+            tie_break,
+            pushed: 0,
+            rng: rand::weak_rng(),
+        }
+    }
+
+}
+
+impl OpenList for Heap {
+    fn push(&mut self, node: Arc<Node>) {
+        self.pushed += 1;
+        let tie = match self.tie_break {
+            TieBreak::Arbitrary => 0,
+            TieBreak::HighG => node.cost as u64,
+            TieBreak::LowH => u64::MAX - node.heuristic as u64,
+            TieBreak::Fifo => u64::MAX - self.pushed,
+            TieBreak::Lifo => self.pushed,
+            TieBreak::Random => self.rng.gen(),
+        };
+        self.heap.push(Entry { node, tie });
+    }
+
+    fn pop(&mut self) -> Option<Arc<Node>> {
+        self.heap.pop().map(|e| e.node)
+    }
+}
+
+/// Buckets indexed by priority, then by g when ties are broken on g. Push is
+/// O(1), pop is amortized O(1) as the minimum priority only moves backwards
+/// when an inconsistent heuristic produces a child cheaper than its parent.
+pub struct Buckets {
+    buckets: Vec<Vec<VecDeque<Arc<Node>>>>,
+    min: usize,
+    len: usize,
+    tie_break: TieBreak,
+    rng: XorShiftRng,
+}
+
+impl Buckets {
+    pub fn new(tie_break: TieBreak) -> Buckets {
+        Buckets {
+            buckets: Vec::new(),
+            min: 0,
+            len: 0,
+            tie_break,
+            rng: rand::weak_rng(),
+        }
+    }
+}
+
+impl OpenList for Buckets {
+    fn push(&mut self, node: Arc<Node>) {
+        let f = priority(&node);
+        // With f fixed, a higher g is a lower h: both orders share the g index.
+        let g = match self.tie_break {
+            TieBreak::HighG | TieBreak::LowH => node.cost,
+            _ => 0,
+        };
+        if f >= self.buckets.len() {
+            self.buckets.resize_with(f + 1, Vec::new);
+        }
+        let bucket = &mut self.buckets[f];
+        if g >= bucket.len() {
+            bucket.resize_with(g + 1, VecDeque::new);
+        }
+        bucket[g].push_back(node);
+        self.len += 1;
+        if f < self.min {
+            self.min = f;
+        }
+    }
+
+    fn pop(&mut self) -> Option<Arc<Node>> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.buckets[self.min].is_empty() {
+            self.min += 1;
+        }
+
+        let bucket = &mut self.buckets[self.min];
+        let deque = bucket.last_mut().expect("non empty bucket");
+        let node = match self.tie_break {
+            TieBreak::Fifo => deque.pop_front(),
+            TieBreak::Random => {
+                let i = self.rng.gen_range(0, deque.len());
+                deque.swap_remove_back(i)
+            }
+            _ => deque.pop_back(),
+        };
+        // Keep the highest non empty g last
+        while bucket.last().map(|d| d.is_empty()).unwrap_or(false) {
+            bucket.pop();
+        }
+        self.len -= 1;
+        node
+    }
+}
+
+// The expected orders below assume nodes ordered on f
+#[cfg(all(test, feature = "uniform"))]
+mod test {
+    use super::{Queue, TieBreak};
+    use node::Node;
+    use std::sync::Arc;
+
+    fn node(cost: usize, heuristic: usize) -> Arc<Node> {
+        Arc::new(Node {
+            board: vec![cost, heuristic],
+            len: 1,
+            cost,
+            heuristic,
+            parents: None,
+        })
+    }
+
+    fn order(queue: Queue, tie_break: TieBreak) -> Vec<usize> {
+        let mut open = super::build(queue, tie_break);
+        for &(g, h) in &[(1, 4), (3, 2), (2, 3), (0, 9)] {
+            open.push(node(g, h));
+        }
+        let mut res = Vec::new();
+        while let Some(n) = open.pop() {
+            res.push(n.cost);
+        }
+        res
+    }
+
+    #[test]
+    fn tie_break_test() {
+        for &queue in &[Queue::Heap, Queue::Buckets] {
+            assert!(order(queue, TieBreak::HighG) == vec![3, 2, 1, 0]);
+            assert!(order(queue, TieBreak::LowH) == vec![3, 2, 1, 0]);
+            assert!(order(queue, TieBreak::Fifo) == vec![1, 3, 2, 0]);
+            assert!(order(queue, TieBreak::Lifo) == vec![2, 3, 1, 0]);
+            assert!(order(queue, TieBreak::Random).len() == 4);
+        }
+    }
+}
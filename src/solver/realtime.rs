@@ -0,0 +1,179 @@
+//! Real-time search: the agent looks ahead with A* from its current board
+//! within a node or time budget, commits to the first move towards the best
+//! board of the lookahead, and learns from it. Expanded boards get their
+//! heuristic raised to the best f of the lookahead minus their own g (as in
+//! RTAA*), so repeated trials from the same start converge towards an
+//! optimal path when the heuristic is admissible.
+
+use heuristics::Heuristic;
+use node::Node;
+use solver::{self, Stats};
+use std::cmp::{self, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Moves after which a trial is given up.
+const MAX_MOVES: usize = 100_000;
+
+pub struct Config {
+    /// Expansions allowed before each move.
+    pub nodes: usize,
+    /// Time allowed before each move, on top of the node budget.
+    pub time: Option<Duration>,
+    pub trials: usize,
+}
+
+struct Agent<'a> {
+    h: &'a Heuristic,
+    len: usize,
+    goal: Vec<usize>,
+    learned: HashMap<Vec<usize>, usize>,
+    expanded: usize,
+}
+
+impl<'a> Agent<'a> {
+    fn estimate(&self, n: &Node) -> usize {
+        cmp::max(n.heuristic, self.learned.get(&n.board).cloned().unwrap_or(0))
+    }
+
+    /// Looks ahead from `board`, updates the learned heuristic and returns the
+    /// board to move to.
+    fn step(&mut self, board: &[usize], config: &Config) -> Option<Vec<usize>> {
+        let started = Instant::now();
+        let mut root = Node {
+            board: board.to_vec(),
+            len: self.len,
+            cost: 0,
+            heuristic: 0,
+            parents: None,
+        };
+        root.heuristic = self.h.eval(&root);
+
+        let mut open = BinaryHeap::new();
+        // Board -> (g, first move on the path from the root)
+        let mut seen: HashMap<Vec<usize>, (usize, Option<Vec<usize>>)> = HashMap::new();
+        let mut closed: Vec<(Vec<usize>, usize)> = Vec::new();
+        open.push(Reverse((self.estimate(&root), Reverse(0), root.board.clone())));
+        seen.insert(root.board.clone(), (0, None));
+
+        let mut best = None;
+        while let Some(Reverse((f, Reverse(g), b))) = open.pop() {
+            if seen[&b].0 != g {
+                continue;
+            }
+            let out_of_budget = closed.len() >= config.nodes
+                || config.time.map(|t| started.elapsed() >= t).unwrap_or(false);
+            if b == self.goal || (out_of_budget && !closed.is_empty()) {
+                best = Some((f, b));
+                break;
+            }
+
+            let first = seen[&b].1.clone();
+            let parent = Arc::new(Node {
+                board: b.clone(),
+                len: self.len,
+                cost: g,
+                heuristic: 0,
+                parents: None,
+            });
+            for child in Node::get_next_steps(&parent, self.h) {
+                if seen.get(&child.board).map(|&(c, _)| c <= child.cost).unwrap_or(false) {
+                    continue;
+                }
+                let f = child.cost + self.estimate(&child);
+                let first = first.clone().or_else(|| Some(child.board.clone()));
+                seen.insert(child.board.clone(), (child.cost, first));
+                open.push(Reverse((f, Reverse(child.cost), child.board)));
+            }
+            closed.push((b, g));
+            self.expanded += 1;
+        }
+
+        let (f, b) = best?;
+        for (board, g) in closed {
+            let value = self.learned.entry(board).or_insert(0);
+            // f may fall below g when h is not consistent, as with manhattan
+            *value = cmp::max(*value, f.saturating_sub(g));
+        }
+        seen.remove(&b).and_then(|(_, first)| first)
+    }
+
+    /// Boards visited by one trial from `start`, or None if it was given up.
+    fn trial(&mut self, start: &[usize], config: &Config) -> Option<Vec<Vec<usize>>> {
+        let mut boards = vec![start.to_vec()];
+        while boards[boards.len() - 1] != self.goal {
+            if boards.len() > MAX_MOVES {
+                return None;
+            }
+            let next = self.step(&boards[boards.len() - 1], config)?;
+            boards.push(next);
+        }
+        Some(boards)
+    }
+}
+
+/// Runs `config.trials` trials sharing what they learned. Returns the
+/// shortest path found and the number of moves of every completed trial.
+pub fn lrta(n: Node, h: &Heuristic, config: &Config) -> (Option<(Arc<Node>, Stats)>, Vec<usize>) {
+    let mut agent = Agent {
+        h,
+        len: n.len,
+        goal: Node::goal(n.len).board,
+        learned: HashMap::new(),
+        expanded: 0,
+    };
+
+    let mut curve = Vec::new();
+    let mut shortest: Option<Vec<Vec<usize>>> = None;
+    for _ in 0..cmp::max(config.trials, 1) {
+        let boards = match agent.trial(&n.board, config) {
+            Some(b) => b,
+            None => break,
+        };
+        curve.push(boards.len() - 1);
+        if shortest.as_ref().map(|s| boards.len() < s.len()).unwrap_or(true) {
+            shortest = Some(boards);
+        }
+    }
+
+    let stats = Stats {
+        total_states: agent.expanded,
+        max_states: agent.learned.len(),
+    };
+    let result = shortest
+        .and_then(|boards| solver::chain(boards, n.len, h))
+        .map(|node| (node, stats));
+    (result, curve)
+}
+
+#[cfg(test)]
+mod test {
+    use heuristics::{self, Heuristic};
+    use node::Node;
+    use solver;
+
+    #[test]
+    fn lrta_learns_towards_optimal() {
+        let h = Heuristic::Base("exact", heuristics::eval_exact);
+        let zero = Heuristic::Base("zero", |_| 0);
+        let config = super::Config {
+            nodes: 10,
+            time: None,
+            trials: 10,
+        };
+        let n: Node = "3\n2 8 3\n1 6 4\n7 0 5\n".parse().expect("Map should be valid");
+        let (optimal, _) = solver::astar(n.clone(), &h).unwrap();
+
+        // With a perfect heuristic the agent never strays.
+        let (found, curve) = super::lrta(n.clone(), &h, &config);
+        assert!(found.unwrap().0.cost == optimal.cost);
+        assert!(curve.iter().all(|&c| c == optimal.cost));
+
+        let (found, curve) = super::lrta(n.clone(), &zero, &config);
+        let (found, _) = found.unwrap();
+        assert!(*found == Node::goal(3));
+        assert!(found.parents().last().map(|s| *s == n).unwrap_or(false));
+        assert!(curve[curve.len() - 1] <= curve[0]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Minimal raw terminal handling for the interactive modes: keys are read
+//! one at a time without echo, and the previous settings are restored when
+//! the `Terminal` is dropped.
+
+use libc;
+use std::io::{self, Write};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    Char(char),
+}
+
+pub struct Terminal {
+    saved: Option<libc::termios>,
+}
+
+impl Terminal {
+    /// Switches stdin to raw mode. When stdin is not a terminal nothing is
+    /// changed and `read_key` only ever times out.
+    pub fn raw() -> Terminal {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 {
+                return Terminal { saved: None };
+            }
+            let mut saved: libc::termios = mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut saved) != 0 {
+                return Terminal { saved: None };
+            }
+            let mut raw = saved;
+            // Without ISIG Ctrl-C arrives as a key, so that the settings
+            // are always restored on the way out
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+
+            print!("\x1b[?25l");
+            let _ = io::stdout().flush();
+            Terminal { saved: Some(saved) }
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Waits for a key press, at most `timeout` when one is given.
+    pub fn read_key(&self, timeout: Option<Duration>) -> Option<Key> {
+        if !self.is_interactive() {
+            if let Some(t) = timeout {
+                ::std::thread::sleep(t);
+            }
+            return None;
+        }
+        read_key(libc::STDIN_FILENO, timeout)
+    }
+}
+
+fn wait(fd: libc::c_int, timeout: Option<Duration>) -> bool {
+    let mut fds = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let ms = timeout.map(|t| (t.as_secs() * 1000 + u64::from(t.subsec_millis())) as libc::c_int).unwrap_or(-1);
+    unsafe { libc::poll(&mut fds, 1, ms) > 0 }
+}
+
+fn read_byte(fd: libc::c_int) -> Option<u8> {
+    let mut byte = 0_u8;
+    match unsafe { libc::read(fd, &mut byte as *mut u8 as *mut libc::c_void, 1) } {
+        1 => Some(byte),
+        _ => None,
+    }
+}
+
+/// Reads one key from `fd`. Bytes are read straight from the descriptor:
+/// a buffered reader would swallow the rest of an escape sequence, and
+/// `poll` would then see nothing left to read.
+fn read_key(fd: libc::c_int, timeout: Option<Duration>) -> Option<Key> {
+    if !wait(fd, timeout) {
+        return None;
+    }
+
+    let first = read_byte(fd)?;
+    if first == 0x03 {
+        // Ctrl-C quits like Escape
+        return Some(Key::Escape);
+    }
+    if first != 0x1b {
+        return Some(Key::Char(first as char));
+    }
+
+    // Arrow keys arrive as ESC [ A..D, a lone ESC is the escape key
+    let pending = || wait(fd, Some(Duration::from_millis(20)));
+    if !pending() || read_byte(fd) != Some(b'[') || !pending() {
+        return Some(Key::Escape);
+    }
+    match read_byte(fd) {
+        Some(b'A') => Some(Key::Up),
+        Some(b'B') => Some(Key::Down),
+        Some(b'C') => Some(Key::Right),
+        Some(b'D') => Some(Key::Left),
+        _ => Some(Key::Escape),
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if let Some(ref saved) = self.saved {
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Turns Ctrl-C into a flag the long running modes poll, until dropped.
+pub struct Interrupt;
+
+impl Interrupt {
+    pub fn catch() -> Interrupt {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        unsafe {
+            libc::signal(libc::SIGINT, on_interrupt as *const () as libc::sighandler_t);
+        }
+        Interrupt
+    }
+
+    pub fn flag(&self) -> &'static AtomicBool {
+        &INTERRUPTED
+    }
+}
+
+impl Drop for Interrupt {
+    fn drop(&mut self) {
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+        }
+    }
+}
+
+/// Moves the cursor back to the top left corner and clears the screen.
+pub fn clear() {
+    print!("\x1b[H\x1b[2J");
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_key, Key};
+    use libc;
+    use std::time::Duration;
+
+    #[test]
+    fn escape_sequence_test() {
+        let mut fds = [0 as libc::c_int; 2];
+        assert!(unsafe { libc::pipe(fds.as_mut_ptr()) } == 0);
+        let input = b"\x1b[Aq\x1b[D\x03\x1b";
+        let written = unsafe { libc::write(fds[1], input.as_ptr() as *const libc::c_void, input.len()) };
+        assert!(written == input.len() as isize);
+
+        let timeout = Some(Duration::from_millis(100));
+        assert!(read_key(fds[0], timeout) == Some(Key::Up));
+        assert!(read_key(fds[0], timeout) == Some(Key::Char('q')));
+        assert!(read_key(fds[0], timeout) == Some(Key::Left));
+        assert!(read_key(fds[0], timeout) == Some(Key::Escape));
+        assert!(read_key(fds[0], timeout) == Some(Key::Escape));
+        assert!(read_key(fds[0], timeout).is_none());
+
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
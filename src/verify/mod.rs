@@ -0,0 +1,256 @@
+//! Independent checker for solutions. A solution is either a string of blank
+//! moves (`UDLR`), a list of the tiles slid into the blank, or any output of
+//! `solve`: grids, moves, json or csv.
+
+use node::{Direction, Node};
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Move {
+    Blank(Direction),
+    Tile(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Move::Blank(Direction::North) => "U".fmt(f),
+            Move::Blank(Direction::South) => "D".fmt(f),
+            Move::Blank(Direction::West) => "L".fmt(f),
+            Move::Blank(Direction::East) => "R".fmt(f),
+            Move::Tile(t) => t.fmt(f),
+        }
+    }
+}
+
+pub enum VerifyError {
+    /// Index of the offending move and why it cannot be played.
+    IllegalMove(usize, String),
+    NotSolved(Node),
+    /// The solution is in none of the accepted formats.
+    Parse(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerifyError::IllegalMove(i, ref reason) => write!(f, "move #{} is illegal: {}", i + 1, reason),
+            VerifyError::NotSolved(_) => "final board is not the goal".fmt(f),
+            VerifyError::Parse(ref reason) => write!(f, "cannot parse solution: {}", reason),
+        }
+    }
+}
+
+/// Removes the ANSI colour codes `solve` wraps around every tile.
+fn strip_colours(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            res.push(c);
+        }
+    }
+
+    res
+}
+
+fn parse_grids(s: &str, start: &Node) -> Option<Result<Vec<Move>, VerifyError>> {
+    let rows = s.lines()
+        .map(|l| l.split_whitespace().map(|v| v.parse::<usize>()).collect::<Result<Vec<_>, _>>())
+        .filter_map(|r| r.ok())
+        .filter(|r| r.len() == start.len)
+        .collect::<Vec<_>>();
+
+    if rows.len() < start.len {
+        return None;
+    }
+
+    let boards = rows.chunks(start.len).map(|c| c.concat()).collect::<Vec<_>>();
+    if boards[0] != start.board {
+        return Some(Err(VerifyError::IllegalMove(0, "first grid is not the puzzle".to_string())));
+    }
+
+    let mut moves = Vec::new();
+    for (i, pair) in boards.windows(2).enumerate() {
+        let blank = match pair[1].iter().position(|&v| v == 0) {
+            Some(p) => p,
+            None => return Some(Err(VerifyError::IllegalMove(i, "grid has no blank".to_string()))),
+        };
+        let tile = pair[0][blank];
+        let mut expected = pair[0].clone();
+        if let Some(old) = expected.iter().position(|&v| v == 0) {
+            expected.swap(old, blank);
+        }
+        if expected != pair[1] {
+            return Some(Err(VerifyError::IllegalMove(i, format!("grid does not follow from sliding tile {}", tile))));
+        }
+        moves.push(Move::Tile(tile));
+    }
+
+    Some(Ok(moves))
+}
+
+/// The `moves` field of `solve -o json`.
+fn json_moves(s: &str) -> Result<String, VerifyError> {
+    let field = "\"moves\":\"";
+    let start = s.find(field).ok_or_else(|| VerifyError::Parse("json has no moves field".to_string()))? + field.len();
+    let len = s[start..].find('"').ok_or_else(|| VerifyError::Parse("unterminated moves field".to_string()))?;
+    Ok(s[start..start + len].to_string())
+}
+
+/// The `move` column of `solve -o csv`, the first row being the start.
+fn csv_moves(s: &str) -> String {
+    s.lines()
+        .skip(1)
+        .filter_map(|l| l.split(',').nth(1))
+        .collect()
+}
+
+pub fn parse(s: &str, start: &Node) -> Result<Vec<Move>, VerifyError> {
+    let mut s = strip_colours(s);
+
+    if s.trim_start().starts_with('{') {
+        s = json_moves(&s)?;
+    } else if s.starts_with("step,move,") {
+        s = csv_moves(&s);
+    } else if let Some(res) = parse_grids(&s, start) {
+        return res;
+    }
+
+    if s.chars().all(|c| c.is_whitespace() || "UDLRudlr".contains(c)) {
+        return Ok(s.chars().filter(|c| !c.is_whitespace()).map(|c| {
+            match c.to_ascii_uppercase() {
+                'U' => Move::Blank(Direction::North),
+                'D' => Move::Blank(Direction::South),
+                'L' => Move::Blank(Direction::West),
+                _ => Move::Blank(Direction::East),
+            }
+        }).collect());
+    }
+
+    s.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .enumerate()
+        .map(|(i, t)| t.parse::<usize>()
+             .map(Move::Tile)
+             .map_err(|_| VerifyError::Parse(format!("'{}' at position {} is not a move", t, i + 1))))
+        .collect()
+}
+
+/// Plays a single move on `board`, returning a description of the problem
+/// when it is not legal from the current blank position.
+pub fn apply(board: &mut [usize], len: usize, m: Move) -> Result<(), String> {
+    let blank = board.iter().position(|&v| v == 0).ok_or_else(|| "board has no blank".to_string())?;
+    let (x, y) = (blank / len, blank % len);
+
+    let target = match m {
+        Move::Blank(Direction::North) if x > 0 => blank - len,
+        Move::Blank(Direction::South) if x + 1 < len => blank + len,
+        Move::Blank(Direction::West) if y > 0 => blank - 1,
+        Move::Blank(Direction::East) if y + 1 < len => blank + 1,
+        Move::Blank(_) => return Err(format!("blank cannot move {} from ({}, {})", m, x, y)),
+        Move::Tile(t) => {
+            let pos = board.iter().position(|&v| v == t && t != 0)
+                .ok_or_else(|| format!("there is no tile {}", t))?;
+            let (tx, ty) = (pos / len, pos % len);
+            if (tx as i64 - x as i64).abs() + (ty as i64 - y as i64).abs() != 1 {
+                return Err(format!("tile {} is not next to the blank", t));
+            }
+            pos
+        }
+    };
+
+    board.swap(blank, target);
+    Ok(())
+}
+
+/// Every board along the solution, starting with `start`.
+pub fn boards(start: &Node, moves: &[Move]) -> Result<Vec<Vec<usize>>, VerifyError> {
+    let mut res = vec![start.board.clone()];
+
+    for (i, &m) in moves.iter().enumerate() {
+        let mut next = res[res.len() - 1].clone();
+        apply(&mut next, start.len, m).map_err(|e| VerifyError::IllegalMove(i, e))?;
+        res.push(next);
+    }
+
+    Ok(res)
+}
+
+pub fn verify(start: &Node, moves: &[Move]) -> Result<Node, VerifyError> {
+    let mut board = start.board.clone();
+
+    for (i, &m) in moves.iter().enumerate() {
+        apply(&mut board, start.len, m).map_err(|e| VerifyError::IllegalMove(i, e))?;
+    }
+
+    let end = Node {
+        board,
+        len: start.len,
+        cost: moves.len(),
+        heuristic: 0,
+        parents: None,
+    };
+
+    if end == Node::goal(start.len) {
+        Ok(end)
+    } else {
+        Err(VerifyError::NotSolved(end))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, verify, VerifyError};
+    use node::Node;
+
+    #[test]
+    fn verify_test() {
+        let n: Node = "3\n1 2 3\n0 8 4\n7 6 5\n".parse().expect("Map should be valid");
+
+        let moves = parse("r", &n).ok().unwrap();
+        assert!(verify(&n, &moves).is_ok());
+
+        let moves = parse("8", &n).ok().unwrap();
+        assert!(verify(&n, &moves).is_ok());
+
+        let grids = "\x1b[37m1  \x1b[0m 2   3\n0   8   4\n7   6   5\n\n1   2   3\n8   0   4\n7   6   5\n\nSolved in 1 moves!\n";
+        let moves = parse(grids, &n).ok().unwrap();
+        assert!(moves.len() == 1);
+        assert!(verify(&n, &moves).is_ok());
+
+        let moves = parse("RLL", &n).ok().unwrap();
+        match verify(&n, &moves) {
+            Err(VerifyError::IllegalMove(2, _)) => {}
+            _ => panic!("third move should be illegal"),
+        }
+
+        let moves = parse("RL", &n).ok().unwrap();
+        match verify(&n, &moves) {
+            Err(VerifyError::NotSolved(_)) => {}
+            _ => panic!("board should not be solved"),
+        }
+
+        match parse("R X", &n) {
+            Err(VerifyError::Parse(_)) => {}
+            _ => panic!("X should not parse"),
+        }
+
+        let json = "{\"size\":3,\"length\":1,\"moves\":\"R\",\"boards\":[[1,2,3,0,8,4,7,6,5],[1,2,3,8,0,4,7,6,5]]}\n";
+        let moves = parse(json, &n).ok().unwrap();
+        assert!(moves.len() == 1);
+        assert!(verify(&n, &moves).is_ok());
+
+        let csv = "step,move,t0,t1,t2,t3,t4,t5,t6,t7,t8\n0,,1,2,3,0,8,4,7,6,5\n1,R,1,2,3,8,0,4,7,6,5\n";
+        let moves = parse(csv, &n).ok().unwrap();
+        assert!(moves.len() == 1);
+        assert!(verify(&n, &moves).is_ok());
+    }
+}